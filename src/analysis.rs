@@ -0,0 +1,396 @@
+use crate::function::Function;
+
+/// Halvings used to pin down a root or intersection between two samples
+const ROOT_BISECTIONS: usize = 60;
+
+/// Golden-section steps used to refine an extremum between three samples
+const EXTREMUM_ITERATIONS: usize = 60;
+
+/// Kind of notable point found on a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FeatureKind {
+    Root,
+    Minimum,
+    Maximum,
+    Intersection,
+}
+
+impl FeatureKind {
+    /// Glyph drawn at the point on the plot
+    pub fn glyph(&self) -> char {
+        match self {
+            FeatureKind::Root => '◆',
+            FeatureKind::Minimum => '▼',
+            FeatureKind::Maximum => '▲',
+            FeatureKind::Intersection => '✕',
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeatureKind::Root => "root",
+            FeatureKind::Minimum => "minimum",
+            FeatureKind::Maximum => "maximum",
+            FeatureKind::Intersection => "intersection",
+        }
+    }
+
+    /// Prefix of the numbered tag that labels the point, e.g. "R1"
+    fn tag_prefix(&self) -> &'static str {
+        match self {
+            FeatureKind::Root => "R",
+            FeatureKind::Minimum => "Min",
+            FeatureKind::Maximum => "Max",
+            FeatureKind::Intersection => "X",
+        }
+    }
+}
+
+/// A root, extremum or intersection, tagged for the plot and the feature table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    pub kind: FeatureKind,
+    pub x: f64,
+    pub y: f64,
+    /// Series the point lies on; "f ∩ g" for intersections
+    pub series: String,
+    /// Short label such as "R1" or "Max2"
+    pub tag: String,
+}
+
+/// Value, relative to the spread of a function's samples, below which an
+/// extremum touches zero and is also a root
+const DOUBLE_ROOT_TOLERANCE: f64 = 1e-9;
+
+/// The labelled functions of one source and the range they were sampled
+/// over, kept so that intersections with other sources can be found
+#[derive(Debug, Clone)]
+pub struct Analyzed {
+    pub functions: Vec<(String, Function)>,
+    pub x_min: f64,
+    pub x_max: f64,
+}
+
+/// Find the roots, interior extrema and pairwise intersections of labelled
+/// functions over a range. Candidates are bracketed from `samples` evenly
+/// spaced evaluations and then refined on the function itself.
+pub fn analyze(functions: &[(String, Function)], x_min: f64, x_max: f64, samples: usize) -> Vec<Feature> {
+    let xs = positions(x_min, x_max, samples);
+    let step = (xs[1] - xs[0]).abs();
+
+    let mut features = Vec::new();
+    for (label, function) in functions {
+        let f = |x: f64| evaluate(function, x);
+        let ys: Vec<Option<f64>> = xs.iter().map(|x| f(*x)).collect();
+        let (low, high) = ys.iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| (lo.min(*y), hi.max(*y)));
+
+        let roots = find_roots(&f, &xs, &ys);
+        for (kind, x) in find_extrema(&f, &xs, &ys) {
+            let Some(y) = f(x) else { continue };
+            // A double root, such as x^2 at 0, touches zero without a sign change
+            if y.abs() <= (high - low) * DOUBLE_ROOT_TOLERANCE && !roots.iter().any(|root| (root - x).abs() <= step) {
+                features.push(untagged(FeatureKind::Root, x, 0.0, label));
+            }
+            features.push(untagged(kind, x, y, label));
+        }
+        for x in roots {
+            features.push(untagged(FeatureKind::Root, x, 0.0, label));
+        }
+    }
+
+    for (i, a) in functions.iter().enumerate() {
+        for b in &functions[i + 1..] {
+            features.extend(intersections(a, b, &xs));
+        }
+    }
+
+    tag_features(features)
+}
+
+/// Intersections between functions of different sources, searched where
+/// the sources' ranges overlap. The features are untagged; tag them together
+/// with the sources' own features.
+pub fn intersect_sources(sources: &[Analyzed], samples: usize) -> Vec<Feature> {
+    let mut features = Vec::new();
+    for (i, a) in sources.iter().enumerate() {
+        for b in &sources[i + 1..] {
+            let (x_min, x_max) = (a.x_min.max(b.x_min), a.x_max.min(b.x_max));
+            if x_min >= x_max {
+                continue;
+            }
+            let xs = positions(x_min, x_max, samples);
+            for function_a in &a.functions {
+                for function_b in &b.functions {
+                    features.extend(intersections(function_a, function_b, &xs));
+                }
+            }
+        }
+    }
+    features
+}
+
+/// Evenly spaced positions across a range, at least three
+fn positions(x_min: f64, x_max: f64, samples: usize) -> Vec<f64> {
+    let count = samples.max(3);
+    (0..count)
+        .map(|i| x_min + (x_max - x_min) * i as f64 / (count - 1) as f64)
+        .collect()
+}
+
+fn evaluate(function: &Function, x: f64) -> Option<f64> {
+    function.evaluate(x).ok().filter(|y| f64::is_finite(*y))
+}
+
+/// Points where two labelled functions cross, bracketed at `xs`
+fn intersections((label_a, a): &(String, Function), (label_b, b): &(String, Function), xs: &[f64]) -> Vec<Feature> {
+    let difference = |x: f64| Some(evaluate(a, x)? - evaluate(b, x)?);
+    let ys: Vec<Option<f64>> = xs.iter().map(|x| difference(*x)).collect();
+    find_roots(&difference, xs, &ys).into_iter()
+        .filter_map(|x| {
+            let y = evaluate(a, x)?;
+            Some(untagged(FeatureKind::Intersection, x, y, &format!("{} ∩ {}", label_a, label_b)))
+        })
+        .collect()
+}
+
+fn untagged(kind: FeatureKind, x: f64, y: f64, series: &str) -> Feature {
+    Feature { kind, x, y, series: series.to_string(), tag: String::new() }
+}
+
+/// Sort by kind then x and number each kind from left to right
+pub fn tag_features(mut features: Vec<Feature>) -> Vec<Feature> {
+    features.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.x.total_cmp(&b.x)));
+    let mut number = 0;
+    let mut previous = None;
+    for feature in &mut features {
+        number = if previous == Some(feature.kind) { number + 1 } else { 1 };
+        previous = Some(feature.kind);
+        feature.tag = format!("{}{}", feature.kind.tag_prefix(), number);
+    }
+    features
+}
+
+/// Sample points where f is zero or changes sign, refined by bisection. A sign
+/// change that grows rather than shrinks under bisection is a pole, not a root.
+fn find_roots(f: &impl Fn(f64) -> Option<f64>, xs: &[f64], ys: &[Option<f64>]) -> Vec<f64> {
+    let mut roots = Vec::new();
+    for i in 0..xs.len() {
+        let Some(y) = ys[i] else { continue };
+        if y == 0.0 {
+            roots.push(xs[i]);
+            continue;
+        }
+
+        let Some(Some(next)) = ys.get(i + 1) else { continue };
+        if *next == 0.0 || y.signum() == next.signum() {
+            continue;
+        }
+
+        let (mut left, mut right) = (xs[i], xs[i + 1]);
+        for _ in 0..ROOT_BISECTIONS {
+            let mid = (left + right) / 2.0;
+            let Some(mid_y) = f(mid) else { break };
+            if mid_y == 0.0 {
+                left = mid;
+                right = mid;
+                break;
+            }
+            if mid_y.signum() == y.signum() {
+                left = mid;
+            } else {
+                right = mid;
+            }
+        }
+
+        let root = (left + right) / 2.0;
+        if f(root).is_some_and(|value| value.abs() <= y.abs().min(next.abs())) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+/// Interior samples higher (or lower) than both neighbours, refined by a
+/// golden-section search between those neighbours
+fn find_extrema(f: &impl Fn(f64) -> Option<f64>, xs: &[f64], ys: &[Option<f64>]) -> Vec<(FeatureKind, f64)> {
+    let mut extrema = Vec::new();
+    for i in 1..xs.len().saturating_sub(1) {
+        let (Some(before), Some(here), Some(after)) = (ys[i - 1], ys[i], ys[i + 1]) else {
+            continue;
+        };
+
+        let kind = if here > before && here >= after {
+            FeatureKind::Maximum
+        } else if here < before && here <= after {
+            FeatureKind::Minimum
+        } else {
+            continue;
+        };
+
+        // Search for the maximum of f, or of -f for a minimum
+        let sign = if kind == FeatureKind::Maximum { 1.0 } else { -1.0 };
+        let g = |x: f64| f(x).map_or(f64::NEG_INFINITY, |y| sign * y);
+        let x = golden_section_max(g, xs[i - 1], xs[i + 1]);
+        extrema.push((kind, if g(x) >= sign * here { x } else { xs[i] }));
+    }
+    extrema
+}
+
+fn golden_section_max(g: impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let (mut gc, mut gd) = (g(c), g(d));
+    for _ in 0..EXTREMUM_ITERATIONS {
+        if gc > gd {
+            b = d;
+            d = c;
+            gd = gc;
+            c = b - ratio * (b - a);
+            gc = g(c);
+        } else {
+            a = c;
+            c = d;
+            gc = gd;
+            d = a + ratio * (b - a);
+            gd = g(d);
+        }
+    }
+    (a + b) / 2.0
+}
+
+/// Table of features, one per line, for printing below the chart
+pub fn format_table(features: &[Feature]) -> String {
+    if features.is_empty() {
+        return "\nNo roots, extrema or intersections found\n".to_string();
+    }
+
+    let rows: Vec<[String; 5]> = features.iter()
+        .map(|f| [
+            format!("{} {}", f.kind.glyph(), f.tag),
+            f.kind.name().to_string(),
+            f.series.clone(),
+            format_coordinate(f.x),
+            format_coordinate(f.y),
+        ])
+        .collect();
+    let headers = ["", "kind", "series", "x", "y"];
+
+    let mut widths = headers.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut output = String::from("\n");
+    for row in std::iter::once(headers.map(String::from)).chain(rows) {
+        let line = format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {:>w3$}  {:>w4$}",
+            row[0], row[1], row[2], row[3], row[4],
+            w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3], w4 = widths[4],
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Four decimals, without a negative sign on values that round to zero
+fn format_coordinate(value: f64) -> String {
+    let formatted = format!("{:.4}", value);
+    match formatted.strip_prefix('-') {
+        Some(rest) if rest.chars().all(|c| c == '0' || c == '.') => rest.to_string(),
+        _ => formatted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn labelled(expression: &str) -> (String, Function) {
+        (expression.to_string(), Function::new(expression))
+    }
+
+    fn of_kind(features: &[Feature], kind: FeatureKind) -> Vec<f64> {
+        features.iter().filter(|f| f.kind == kind).map(|f| f.x).collect()
+    }
+
+    #[test]
+    fn test_roots_and_extrema_of_sine() {
+        let features = analyze(&[labelled("sin(x)")], -4.0, 4.0, 50);
+
+        let roots = of_kind(&features, FeatureKind::Root);
+        assert_eq!(roots.len(), 3);
+        for (root, expected) in roots.iter().zip([-PI, 0.0, PI]) {
+            assert!((root - expected).abs() < 1e-9, "root {}", root);
+        }
+
+        let maxima = of_kind(&features, FeatureKind::Maximum);
+        let minima = of_kind(&features, FeatureKind::Minimum);
+        assert!(maxima.len() == 1 && (maxima[0] - PI / 2.0).abs() < 1e-6);
+        assert!(minima.len() == 1 && (minima[0] + PI / 2.0).abs() < 1e-6);
+
+        let tags: Vec<&str> = features.iter().map(|f| f.tag.as_str()).collect();
+        assert_eq!(tags, vec!["R1", "R2", "R3", "Min1", "Max1"]);
+    }
+
+    #[test]
+    fn test_poles_are_not_roots() {
+        let features = analyze(&[labelled("tan(x)")], -3.0, 3.0, 100);
+        let roots = of_kind(&features, FeatureKind::Root);
+        assert_eq!(roots.len(), 1);
+        assert!(roots[0].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_intersections() {
+        let features = analyze(&[labelled("x^2"), labelled("x + 2")], -3.0, 3.0, 40);
+        let crossings: Vec<&Feature> = features.iter()
+            .filter(|f| f.kind == FeatureKind::Intersection)
+            .collect();
+
+        assert_eq!(crossings.len(), 2);
+        assert!((crossings[0].x + 1.0).abs() < 1e-9 && (crossings[0].y - 1.0).abs() < 1e-9);
+        assert!((crossings[1].x - 2.0).abs() < 1e-9 && (crossings[1].y - 4.0).abs() < 1e-9);
+        assert_eq!(crossings[0].series, "x^2 ∩ x + 2");
+    }
+
+    #[test]
+    fn test_double_roots() {
+        let features = analyze(&[labelled("x^2")], -3.0, 3.0, 40);
+        let roots = of_kind(&features, FeatureKind::Root);
+        assert!(roots.len() == 1 && roots[0].abs() < 1e-6, "{:?}", roots);
+        assert_eq!(of_kind(&features, FeatureKind::Minimum).len(), 1);
+
+        // Sampled exactly at zero, the root is found once
+        assert_eq!(of_kind(&analyze(&[labelled("x^2")], -3.0, 3.0, 41), FeatureKind::Root).len(), 1);
+        assert!(of_kind(&analyze(&[labelled("x^2 + 1")], -3.0, 3.0, 40), FeatureKind::Root).is_empty());
+    }
+
+    #[test]
+    fn test_intersections_between_sources() {
+        let sources = [
+            Analyzed { functions: vec![labelled("x^2")], x_min: -3.0, x_max: 3.0 },
+            Analyzed { functions: vec![labelled("x + 2")], x_min: 0.0, x_max: 10.0 },
+        ];
+        let crossings = intersect_sources(&sources, 40);
+        // Only where both sources were plotted
+        assert_eq!(crossings.len(), 1);
+        assert!((crossings[0].x - 2.0).abs() < 1e-9);
+        assert_eq!(crossings[0].series, "x^2 ∩ x + 2");
+    }
+
+    #[test]
+    fn test_format_table() {
+        let table = format_table(&analyze(&[labelled("x^2 - 1")], -2.0, 2.0, 21));
+        let lines: Vec<&str> = table.trim_start_matches('\n').lines().collect();
+        assert_eq!(lines[0], "        kind     series         x        y");
+        assert_eq!(lines[1], "◆ R1    root     x^2 - 1  -1.0000   0.0000");
+        assert_eq!(lines[3], "▼ Min1  minimum  x^2 - 1   0.0000  -1.0000");
+    }
+}
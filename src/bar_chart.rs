@@ -1,5 +1,5 @@
 use crate::coordinates::{NumericCoordinate, DataBounds, CoordinateTransformer, CategoricalTransformer};
-use crate::layout::LayoutEngine;
+use crate::layout::{LayoutEngine, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT};
 use crate::layered_canvas::{LayeredCanvas, RenderPriority};
 use crate::data::{Dataset, DataPoint};
 use crate::ticks::CategoricalTickGenerator;
@@ -22,7 +22,7 @@ impl BarChart {
         Self {
             width,
             height,
-            data: dataset.points.clone(),
+            data: dataset.points.iter().filter(|p| !p.is_gap()).cloned().collect(),
             title: title.to_string(),
             x_label: dataset.x_label.clone(),
             y_label: dataset.y_label.clone(),
@@ -85,7 +85,7 @@ impl BarChart {
             bounds,
             self.width,
             self.height,
-            layout.margins.clone(),
+            layout.margins,
         );
         
         // Create layered canvas
@@ -114,7 +114,7 @@ impl BarChart {
             bounds,
             self.width,
             self.height,
-            layout.margins.clone(),
+            layout.margins,
         );
         
         // Create layered canvas
@@ -156,11 +156,7 @@ impl BarChart {
                     let bar_height = if bar_bottom > bar_top { bar_bottom - bar_top } else { 1 };
                     
                     // Calculate bar position (center around the x coordinate)
-                    let bar_left = if screen_point.col >= self.bar_width / 2 {
-                        screen_point.col - self.bar_width / 2
-                    } else {
-                        0
-                    };
+                    let bar_left = screen_point.col.saturating_sub(self.bar_width / 2);
                     
                     // Draw the bar
                     for col in bar_left..bar_left + self.bar_width {
@@ -353,11 +349,7 @@ impl BarChart {
                     let bar_height = if bar_bottom > bar_top { bar_bottom - bar_top } else { 1 };
                     
                     // Calculate bar position (center around the x coordinate)
-                    let bar_left = if screen_point.col >= self.bar_width / 2 {
-                        screen_point.col - self.bar_width / 2
-                    } else {
-                        0
-                    };
+                    let bar_left = screen_point.col.saturating_sub(self.bar_width / 2);
                     
                     // Draw the bar
                     for col in bar_left..bar_left + self.bar_width {
@@ -391,7 +383,7 @@ impl BarChart {
                     self.calculate_categorical_bounds(),
                     self.width,
                     self.height,
-                    layout.margins.clone(),
+                    layout.margins,
                 );
                 if let Some(screen_point) = coord_transformer.data_to_screen(coord_point) {
                     axes_layer.draw_point(screen_point.col, area.top + area.height, '┬');
@@ -414,7 +406,7 @@ impl BarChart {
                     self.calculate_categorical_bounds(),
                     self.width,
                     self.height,
-                    layout.margins.clone(),
+                    layout.margins,
                 );
                 if let Some(screen_point) = coord_transformer.data_to_screen(coord_point) {
                     let label_start = screen_point.col.saturating_sub(tick.label.len() / 2);
@@ -439,7 +431,7 @@ impl BarChart {
 }
 
 pub fn render_bar_chart(dataset: &Dataset, title: &str, bar_char: char, bar_width: usize, color: Option<&str>) -> String {
-    let plot = BarChart::new(dataset, title, 80, 24)
+    let plot = BarChart::new(dataset, title, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT)
         .with_bar_char(bar_char)
         .with_bar_width(bar_width);
     plot.render(color)
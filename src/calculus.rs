@@ -0,0 +1,160 @@
+use crate::data::DataPoint;
+
+/// Numerical derivative of sampled numeric points in x order. Each sample takes
+/// the slope of the parabola through it and its neighbours (central differences,
+/// one-sided at the ends of a segment), so uneven adaptive spacing is handled.
+/// Gap markers are kept and nothing is differenced across them.
+pub fn derivative(points: &[DataPoint]) -> Vec<DataPoint> {
+    let mut result = Vec::with_capacity(points.len());
+    for (segment, gap) in segments(points) {
+        let samples: Vec<(f64, f64)> = segment.iter()
+            .filter_map(|p| Some((p.x_numeric()?, p.y())))
+            .collect();
+
+        match samples.len() {
+            0 => {}
+            1 => result.push(DataPoint::gap(samples[0].0)),
+            2 => {
+                let slope = (samples[1].1 - samples[0].1) / (samples[1].0 - samples[0].0);
+                result.extend(samples.iter().map(|(x, _)| DataPoint::Numeric(*x, slope)));
+            }
+            n => {
+                for i in 0..n {
+                    // Interior points are centred; the ends borrow the nearest triple
+                    let first = i.saturating_sub(1).min(n - 3);
+                    let slope = quadratic_slope(&samples[first..first + 3], samples[i].0);
+                    result.push(DataPoint::Numeric(samples[i].0, slope));
+                }
+            }
+        }
+        result.extend(gap.cloned());
+    }
+    result
+}
+
+/// Cumulative integral of sampled numeric points in x order, starting from zero
+/// at the first sample. Pairs of intervals are integrated with Simpson's rule,
+/// each interval under the same parabola so running totals are available at
+/// every sample. Undefined stretches between gap markers contribute nothing.
+pub fn integral(points: &[DataPoint]) -> Vec<DataPoint> {
+    let mut result = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    for (segment, gap) in segments(points) {
+        let samples: Vec<(f64, f64)> = segment.iter()
+            .filter_map(|p| Some((p.x_numeric()?, p.y())))
+            .collect();
+
+        if let Some(first) = samples.first() {
+            result.push(DataPoint::Numeric(first.0, total));
+        }
+        for i in 0..samples.len().saturating_sub(1) {
+            let third = if i % 2 == 0 && i + 2 < samples.len() {
+                Some(samples[i + 2])
+            } else if i % 2 == 1 {
+                Some(samples[i - 1])
+            } else {
+                // A final lone interval after an even run borrows the point before it
+                i.checked_sub(1).map(|j| samples[j])
+            };
+            total += quadratic_area(samples[i], samples[i + 1], third);
+            result.push(DataPoint::Numeric(samples[i + 1].0, total));
+        }
+        result.extend(gap.cloned());
+    }
+    result
+}
+
+/// Split points into runs between gap markers, each with the gap that ends it
+fn segments(points: &[DataPoint]) -> impl Iterator<Item = (&[DataPoint], Option<&DataPoint>)> {
+    points.split_inclusive(DataPoint::is_gap).map(|chunk| match chunk.split_last() {
+        Some((last, rest)) if last.is_gap() => (rest, Some(last)),
+        _ => (chunk, None),
+    })
+}
+
+/// Divided differences of the parabola through three points, in Newton form:
+/// p(x) = y0 + d1 (x - x0) + d2 (x - x0)(x - x1)
+fn newton_coefficients(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> (f64, f64) {
+    let d1 = (b.1 - a.1) / (b.0 - a.0);
+    let d2 = ((c.1 - b.1) / (c.0 - b.0) - d1) / (c.0 - a.0);
+    (d1, d2)
+}
+
+/// Slope at x of the parabola through three samples
+fn quadratic_slope(samples: &[(f64, f64)], x: f64) -> f64 {
+    let (a, b, c) = (samples[0], samples[1], samples[2]);
+    let (d1, d2) = newton_coefficients(a, b, c);
+    d1 + d2 * (2.0 * x - a.0 - b.0)
+}
+
+/// Area from a to b under the parabola through a, b and a third sample on
+/// either side, or under the straight segment when there is no third sample
+fn quadratic_area(a: (f64, f64), b: (f64, f64), third: Option<(f64, f64)>) -> f64 {
+    let h = b.0 - a.0;
+    let (d1, d2) = match third {
+        Some(c) => newton_coefficients(a, b, c),
+        None => ((b.1 - a.1) / h, 0.0),
+    };
+    a.1 * h + d1 * h * h / 2.0 - d2 * h * h * h / 6.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(f: impl Fn(f64) -> f64, xs: &[f64]) -> Vec<DataPoint> {
+        xs.iter().map(|x| DataPoint::Numeric(*x, f(*x))).collect()
+    }
+
+    fn values(points: &[DataPoint]) -> Vec<f64> {
+        points.iter().map(DataPoint::y).collect()
+    }
+
+    #[test]
+    fn test_derivative_is_exact_for_parabolas() {
+        // Uneven spacing, as produced by adaptive sampling
+        let xs = [0.0, 0.5, 0.7, 1.5, 2.0, 3.2];
+        let slopes = derivative(&sample(|x| x * x - 3.0 * x, &xs));
+        for (x, slope) in xs.iter().zip(values(&slopes)) {
+            assert!((slope - (2.0 * x - 3.0)).abs() < 1e-9, "slope at {} was {}", x, slope);
+        }
+    }
+
+    #[test]
+    fn test_integral_matches_simpson() {
+        // Simpson's rule is exact for cubics at even sample counts
+        let xs: Vec<f64> = (0..=10).map(|i| i as f64 * 0.2).collect();
+        let area = integral(&sample(|x| x * x * x, &xs));
+        assert_eq!(area[0].y(), 0.0);
+        assert!((area[10].y() - 4.0).abs() < 1e-9);
+        assert!((area[4].y() - 0.8_f64.powi(4) / 4.0).abs() < 1e-9);
+
+        // Odd interval counts stay close
+        let area = integral(&sample(f64::sin, &xs[..10]));
+        assert!((area[9].y() - (1.0 - 1.8_f64.cos())).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_gaps_are_kept() {
+        let mut points = sample(|x| x, &[0.0, 1.0, 2.0]);
+        points.push(DataPoint::gap(2.5));
+        points.extend(sample(|x| 2.0 * x, &[3.0, 4.0]));
+        points.push(DataPoint::gap(4.5));
+        points.push(DataPoint::Numeric(5.0, 1.0));
+
+        let slopes = derivative(&points);
+        assert_eq!(slopes.len(), points.len());
+        assert_eq!(values(&slopes[..3]), vec![1.0, 1.0, 1.0]);
+        assert!(slopes[3].is_gap());
+        assert_eq!(values(&slopes[4..6]), vec![2.0, 2.0]);
+        // A lone sample has no slope
+        assert!(slopes[6].is_gap() && slopes[7].is_gap());
+
+        // The integral resumes from its running total after each gap
+        let area = integral(&points);
+        assert_eq!(values(&area[..3]), vec![0.0, 0.5, 2.0]);
+        assert!(area[3].is_gap());
+        assert_eq!(values(&area[4..6]), vec![2.0, 9.0]);
+        assert_eq!(area[7].y(), 9.0);
+    }
+}
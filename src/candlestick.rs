@@ -0,0 +1,321 @@
+use crate::coordinates::{NumericCoordinate, DataBounds, CoordinateTransformer};
+use crate::layout::{LayoutEngine, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT};
+use crate::layered_canvas::{Canvas, LayeredCanvas, RenderPriority};
+use crate::data::{Candle, OhlcDataset};
+
+/// Rows given to the volume sub-panel below the price chart
+const VOLUME_ROWS: usize = 5;
+
+/// Partial blocks used to give volume bars sub-row resolution
+const VOLUME_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct CandlestickChart {
+    width: usize,
+    height: usize,
+    candles: Vec<Candle>,
+    title: String,
+    x_label: String,
+    y_label: String,
+    show_volume: bool,
+    up_color: String,
+    down_color: String,
+}
+
+impl CandlestickChart {
+    pub fn new(dataset: &OhlcDataset, title: &str, width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            candles: dataset.candles.clone(),
+            title: title.to_string(),
+            x_label: dataset.time_label.clone(),
+            y_label: "Price".to_string(),
+            show_volume: false,
+            up_color: "green".to_string(),
+            down_color: "red".to_string(),
+        }
+    }
+
+    pub fn with_volume(mut self, show_volume: bool) -> Self {
+        self.show_volume = show_volume;
+        self
+    }
+
+    pub fn with_colors(mut self, up_color: &str, down_color: &str) -> Self {
+        self.up_color = up_color.to_string();
+        self.down_color = down_color.to_string();
+        self
+    }
+
+    pub fn render(&self) -> String {
+        if self.candles.is_empty() {
+            return format!("{}\n\nNo data to plot\n", self.title);
+        }
+
+        let bounds = self.calculate_bounds();
+        let layout_engine = LayoutEngine::new(self.width, self.height).with_time_x_axis(true);
+        let layout = layout_engine.calculate_layout(&bounds);
+
+        let transformer = CoordinateTransformer::new(
+            bounds,
+            self.width,
+            self.height,
+            layout.margins,
+        );
+
+        let mut canvas = LayeredCanvas::new(self.width, self.height);
+
+        self.draw_axes(&mut canvas, &layout);
+        self.draw_ticks(&mut canvas, &layout);
+        self.draw_candles(&mut canvas, &transformer);
+
+        let final_canvas = canvas.flatten();
+        let mut output = String::new();
+        output.push_str(&self.title);
+        output.push_str("\n\n");
+        output.push_str(&self.y_label);
+        output.push('\n');
+        output.push_str(&self.render_with_y_labels(&final_canvas, &layout));
+        if self.show_volume {
+            output.push_str(&self.render_volume_panel(&transformer, &layout));
+        }
+        output.push_str(&crate::layout::format_x_axis_label(&self.x_label, &layout));
+
+        output
+    }
+
+    fn calculate_bounds(&self) -> DataBounds {
+        let min_x = self.candles.iter().map(|c| c.time).fold(f64::INFINITY, f64::min);
+        let max_x = self.candles.iter().map(|c| c.time).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = self.candles.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+        let max_y = self.candles.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max);
+
+        // Pad by roughly one candle interval so the outermost candles clear the axes
+        let x_range = max_x - min_x;
+        let y_range = max_y - min_y;
+        let x_padding = if x_range > 0.0 { x_range / self.candles.len() as f64 } else { 86400.0 };
+        let y_padding = if y_range > 0.0 { y_range * 0.1 } else { 1.0 };
+
+        DataBounds {
+            min_x: min_x - x_padding,
+            max_x: max_x + x_padding,
+            min_y: min_y - y_padding,
+            max_y: max_y + y_padding,
+        }
+    }
+
+    fn draw_candles(&self, canvas: &mut LayeredCanvas, transformer: &CoordinateTransformer) {
+        for candle in &self.candles {
+            let to_screen = |price: f64| transformer.data_to_screen(NumericCoordinate::new(candle.time, price));
+            let (Some(high), Some(low), Some(open), Some(close)) =
+                (to_screen(candle.high), to_screen(candle.low), to_screen(candle.open), to_screen(candle.close))
+            else {
+                continue;
+            };
+
+            let color = Some(self.candle_color(candle));
+
+            // Wick spans the full high-low range underneath the body
+            let wick_layer = canvas.get_layer(RenderPriority::Lines);
+            for row in high.row..=low.row {
+                wick_layer.draw_point_with_color(high.col, row, '│', color);
+            }
+
+            // Body spans open to close; a flat body is drawn as a single bar
+            let body_layer = canvas.get_layer(RenderPriority::Points);
+            if open.row == close.row {
+                body_layer.draw_point_with_color(open.col, open.row, '━', color);
+            } else {
+                for row in open.row.min(close.row)..=open.row.max(close.row) {
+                    body_layer.draw_point_with_color(open.col, row, '┃', color);
+                }
+            }
+        }
+    }
+
+    fn candle_color(&self, candle: &Candle) -> &str {
+        if candle.is_up() {
+            &self.up_color
+        } else {
+            &self.down_color
+        }
+    }
+
+    fn render_volume_panel(&self, transformer: &CoordinateTransformer, layout: &crate::layout::Layout) -> String {
+        let max_volume = self.candles.iter()
+            .filter_map(|c| c.volume)
+            .fold(0.0, f64::max);
+        if max_volume <= 0.0 {
+            return String::new();
+        }
+
+        let mut panel = Canvas::new(self.width, VOLUME_ROWS);
+        for candle in &self.candles {
+            let Some(volume) = candle.volume else { continue };
+            let Some(screen) = transformer.data_to_screen(NumericCoordinate::new(candle.time, candle.low)) else {
+                continue;
+            };
+
+            // Height in eighths of a row, filled from the bottom of the panel
+            let eighths = (volume / max_volume * (VOLUME_ROWS * 8) as f64).round() as usize;
+            let color = Some(self.candle_color(candle));
+            for level in 0..VOLUME_ROWS {
+                let filled = eighths.saturating_sub(level * 8).min(8);
+                if filled > 0 {
+                    panel.draw_point_with_color(screen.col, VOLUME_ROWS - 1 - level, VOLUME_BLOCKS[filled - 1], color);
+                }
+            }
+        }
+
+        let max_label_width = layout.y_ticks.iter()
+            .map(|(_, tick)| tick.label.len())
+            .max()
+            .unwrap_or(0);
+        let indent = " ".repeat(max_label_width + 1);
+
+        let mut output = format!("\nVolume (max {})\n", max_volume);
+        for line in panel.to_string().lines() {
+            output.push_str(&indent);
+            output.push_str(line);
+            output.push('\n');
+        }
+        output
+    }
+
+    fn draw_axes(&self, canvas: &mut LayeredCanvas, layout: &crate::layout::Layout) {
+        let axes_layer = canvas.get_layer(RenderPriority::Axes);
+        let area = &layout.plot_area;
+
+        // Draw Y axis
+        axes_layer.draw_vertical_line(area.left.saturating_sub(1), area.top, area.top + area.height, '│');
+
+        // Draw X axis
+        axes_layer.draw_line(area.top + area.height, area.left.saturating_sub(1), area.left + area.width, '─');
+
+        // Draw corner
+        axes_layer.draw_point(area.left.saturating_sub(1), area.top + area.height, '└');
+    }
+
+    fn draw_ticks(&self, canvas: &mut LayeredCanvas, layout: &crate::layout::Layout) {
+        let area = &layout.plot_area;
+
+        // Draw X ticks
+        {
+            let axes_layer = canvas.get_layer(RenderPriority::Axes);
+            for (col, _tick) in &layout.x_ticks {
+                axes_layer.draw_point(*col, area.top + area.height, '┬');
+            }
+
+            // Draw Y ticks
+            for (row, _tick) in &layout.y_ticks {
+                axes_layer.draw_point(area.left.saturating_sub(1), *row, '┤');
+            }
+        }
+
+        // Draw X tick labels
+        {
+            let label_layer = canvas.get_layer(RenderPriority::Labels);
+            for (col, tick) in &layout.x_ticks {
+                let label_start = col.saturating_sub(tick.label.len() / 2);
+                label_layer.draw_text(label_start, area.top + area.height + 1, &tick.label);
+            }
+        }
+    }
+
+    fn render_with_y_labels(&self, canvas: &Canvas, layout: &crate::layout::Layout) -> String {
+        let canvas_output = canvas.to_string();
+        let lines: Vec<&str> = canvas_output.lines().collect();
+
+        // Calculate the maximum width of y-axis labels for consistent alignment
+        let max_label_width = layout.y_ticks.iter()
+            .map(|(_, tick)| tick.label.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut output = String::new();
+        let plot_start = layout.plot_area.top;
+        let plot_end = layout.plot_area.top + layout.plot_area.height;
+
+        for row in plot_start..=plot_end {
+            let y_label = layout.y_ticks.iter()
+                .find(|(tick_row, _)| *tick_row == row)
+                .map(|(_, tick)| &tick.label);
+
+            if let Some(label) = y_label {
+                output.push_str(&format!("{:>width$} ", label, width = max_label_width));
+            } else {
+                output.push_str(&" ".repeat(max_label_width + 1));
+            }
+
+            if row < lines.len() {
+                output.push_str(lines[row]);
+            }
+            output.push('\n');
+        }
+
+        // Add x-axis labels below the plot
+        let x_axis_row = plot_end + 1;
+        if x_axis_row < lines.len() {
+            output.push_str(&" ".repeat(max_label_width + 1));
+            output.push_str(lines[x_axis_row]);
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+pub fn render_candlestick_chart(dataset: &OhlcDataset, title: &str, show_volume: bool, up_color: &str, down_color: &str) -> String {
+    let chart = CandlestickChart::new(dataset, title, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT)
+        .with_volume(show_volume)
+        .with_colors(up_color, down_color);
+    chart.render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(day: f64, open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle { time: day * 86400.0, open, high, low, close, volume: Some(day * 100.0) }
+    }
+
+    fn dataset() -> OhlcDataset {
+        OhlcDataset {
+            candles: vec![
+                candle(1.0, 10.0, 14.0, 9.0, 13.0),
+                candle(2.0, 13.0, 13.5, 8.0, 9.0),
+                candle(3.0, 9.0, 12.0, 8.5, 11.0),
+            ],
+            time_label: "Date".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_candles_use_box_drawing() {
+        let chart = CandlestickChart::new(&dataset(), "Prices", 60, 20);
+        let output = chart.render();
+
+        assert!(output.starts_with("Prices\n\nPrice\n"));
+        assert!(output.contains('┃'));
+        assert!(output.contains('│'));
+        assert!(output.contains("01-0"));
+        assert!(!output.contains("Volume"));
+    }
+
+    #[test]
+    fn test_up_and_down_colors() {
+        let chart = CandlestickChart::new(&dataset(), "Prices", 60, 20).with_colors("#00ff00", "#ff0000");
+        assert_eq!(chart.candle_color(&chart.candles[0]), "#00ff00");
+        assert_eq!(chart.candle_color(&chart.candles[1]), "#ff0000");
+    }
+
+    #[test]
+    fn test_volume_panel() {
+        let chart = CandlestickChart::new(&dataset(), "Prices", 60, 20).with_volume(true);
+        let output = chart.render();
+
+        assert!(output.contains("Volume (max 300)"));
+        assert!(output.contains('█'));
+    }
+}
@@ -36,4 +36,34 @@ pub fn apply_color(ch: char, color_str: &str) -> Option<String> {
         "bright_cyan" => Some(ch_str.bright_cyan().to_string()),
         _ => None,
     }
-}
\ No newline at end of file
+}
+/// Default palette for multi-colour plots, cycled when more colours are needed
+pub const PALETTE: &[&str] = &[
+    "blue",
+    "red",
+    "green",
+    "yellow",
+    "magenta",
+    "cyan",
+    "bright_blue",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_magenta",
+    "bright_cyan",
+];
+
+/// Pick the palette colour for the given series or slice index
+pub fn palette_color(index: usize) -> &'static str {
+    PALETTE[index % PALETTE.len()]
+}
+
+/// Colour for the series at `index` out of `count`: a lone series keeps the
+/// user's colour, while multiple series take palette colours (led by the user's colour)
+pub fn series_color(index: usize, count: usize, color: Option<&str>) -> Option<&str> {
+    if count <= 1 || (index == 0 && color.is_some()) {
+        color
+    } else {
+        Some(palette_color(index))
+    }
+}
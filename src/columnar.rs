@@ -0,0 +1,283 @@
+use anyhow::{Result, anyhow};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, Int64Type};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_cast::{can_cast_types, cast};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{DataType, Schema, TimeUnit};
+use parquet::arrow::ProjectionMask;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::file::reader::ChunkReader;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use crate::data::{Dataset, PointCollector};
+use crate::input::{self, Compression, InputFormat, ReadOptions};
+
+/// Leading bytes of an Arrow IPC file, as opposed to a stream
+const ARROW_FILE_MAGIC: &[u8] = b"ARROW1";
+
+/// Plot two columns of a Parquet or Arrow IPC file, decoding only those two.
+/// Column types decide how values are read: numeric x values plot as numbers,
+/// timestamps and dates on a date axis, and strings as categories.
+pub fn parse_columnar(file_path: &str, options: &ReadOptions) -> Result<Dataset> {
+    let mut plot = ColumnPlot::new(file_path, options);
+    // Both readers seek, so stdin and compressed files are read into memory first
+    let in_memory = file_path == input::STDIN_SOURCE || Compression::of_file(file_path)? != Compression::None;
+    match (options.format_for(file_path), in_memory) {
+        (InputFormat::Parquet, false) => read_parquet(File::open(file_path)?, &mut plot)?,
+        (InputFormat::Parquet, true) => read_parquet(bytes::Bytes::from(read_all(file_path)?), &mut plot)?,
+        (_, false) => read_arrow(|| Ok(File::open(file_path)?), &mut plot)?,
+        (_, true) => {
+            let data = read_all(file_path)?;
+            read_arrow(|| Ok(Cursor::new(&data[..])), &mut plot)?
+        }
+    }
+    plot.finish()
+}
+
+fn read_all(file_path: &str) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    input::open_input(file_path)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn read_parquet<T: ChunkReader + 'static>(file: T, plot: &mut ColumnPlot) -> Result<()> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let indices = plot.select(builder.schema())?;
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    for batch in builder.with_projection(mask).build()? {
+        plot.add(&batch?)?;
+    }
+    Ok(())
+}
+
+/// Read an Arrow IPC file or stream. The schema is read first to find the
+/// columns, then the source is opened again with only those projected.
+fn read_arrow<R: Read + Seek>(open: impl Fn() -> Result<R>, plot: &mut ColumnPlot) -> Result<()> {
+    let mut magic = [0; ARROW_FILE_MAGIC.len()];
+    let mut source = open()?;
+    let is_file = source.read_exact(&mut magic).is_ok() && magic == ARROW_FILE_MAGIC;
+    source.seek(SeekFrom::Start(0))?;
+
+    if is_file {
+        let indices = plot.select(&FileReader::try_new(source, None)?.schema())?;
+        for batch in FileReader::try_new(open()?, Some(indices))? {
+            plot.add(&batch?)?;
+        }
+    } else {
+        let indices = plot.select(&StreamReader::try_new(BufReader::new(source), None)?.schema())?;
+        for batch in StreamReader::try_new(BufReader::new(open()?), Some(indices))? {
+            plot.add(&batch?)?;
+        }
+    }
+    Ok(())
+}
+
+/// The chosen x and y columns, and the points read from them so far
+struct ColumnPlot<'a> {
+    file_path: &'a str,
+    options: &'a ReadOptions,
+    x_name: String,
+    y_name: String,
+    collector: PointCollector,
+    is_time: bool,
+    /// Rows read before the current batch, for messages
+    rows: usize,
+}
+
+impl<'a> ColumnPlot<'a> {
+    fn new(file_path: &'a str, options: &'a ReadOptions) -> Self {
+        Self {
+            file_path,
+            options,
+            x_name: String::new(),
+            y_name: String::new(),
+            collector: PointCollector::new().with_downsample(options.downsample),
+            is_time: false,
+            rows: 0,
+        }
+    }
+
+    /// Resolve `--x` and `--y`, defaulting to the first two columns, and
+    /// return the positions of the columns to read
+    fn select(&mut self, schema: &Schema) -> Result<Vec<usize>> {
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        let find = |name: Option<&str>, default: usize| -> Result<usize> {
+            match name {
+                Some(name) => names.iter().position(|n| *n == name).ok_or_else(|| {
+                    anyhow!(
+                        "Column '{}' not found in {}; columns are: {}",
+                        name,
+                        input::display_name(self.file_path),
+                        names.join(", ")
+                    )
+                }),
+                None if default < names.len() => Ok(default),
+                None => Err(anyhow!(
+                    "{} needs at least two columns to plot; columns are: {}",
+                    input::display_name(self.file_path),
+                    names.join(", ")
+                )),
+            }
+        };
+        let x = find(self.options.x.as_deref(), 0)?;
+        let y = find(self.options.y.as_deref(), 1)?;
+        self.x_name = names[x].to_string();
+        self.y_name = names[y].to_string();
+
+        let mut indices = vec![x, y];
+        indices.sort_unstable();
+        indices.dedup();
+        Ok(indices)
+    }
+
+    fn add(&mut self, batch: &RecordBatch) -> Result<()> {
+        let column = |name: &str| batch.column_by_name(name)
+            .ok_or_else(|| anyhow!("Column '{}' missing from a batch of {}", name, input::display_name(self.file_path)));
+        let xs = self.x_values(column(&self.x_name)?)?;
+        let ys = self.y_values(column(&self.y_name)?)?;
+        for ((x_text, x), y) in xs.into_iter().zip(ys) {
+            self.collector.push(&x_text, x, y);
+        }
+        self.rows += batch.num_rows();
+        Ok(())
+    }
+
+    /// x values as the text and number the point collector takes, with
+    /// temporal columns converted to epoch seconds
+    fn x_values(&mut self, column: &ArrayRef) -> Result<Vec<(String, Option<f64>)>> {
+        let data_type = column.data_type();
+        let seconds_per_unit = match data_type {
+            DataType::Timestamp(unit, _) => Some(match unit {
+                TimeUnit::Second => 1.0,
+                TimeUnit::Millisecond => 1e-3,
+                TimeUnit::Microsecond => 1e-6,
+                TimeUnit::Nanosecond => 1e-9,
+            }),
+            DataType::Date32 => Some(86_400.0),
+            DataType::Date64 => Some(1e-3),
+            _ => None,
+        };
+
+        let numbers = |values: Vec<Option<f64>>| values.into_iter()
+            .map(|value| (value.map_or_else(String::new, |v| v.to_string()), value))
+            .collect();
+        if let Some(factor) = seconds_per_unit {
+            self.is_time = true;
+            let raw = cast(column, &DataType::Int64)?;
+            let raw = raw.as_primitive::<Int64Type>();
+            return Ok(numbers(raw.iter().map(|v| v.map(|v| v as f64 * factor)).collect()));
+        }
+        if data_type.is_numeric() {
+            let values = cast(column, &DataType::Float64)?;
+            return Ok(numbers(values.as_primitive::<Float64Type>().iter().collect()));
+        }
+        if can_cast_types(data_type, &DataType::Utf8) {
+            let labels = cast(column, &DataType::Utf8)?;
+            return Ok(labels.as_string::<i32>().iter()
+                .map(|label| (label.unwrap_or_default().to_string(), None))
+                .collect());
+        }
+        Err(anyhow!("Column '{}' has type {}, which cannot be used for x", self.x_name, data_type))
+    }
+
+    /// y values, with nulls as gaps. Strings are parsed as numbers; one that
+    /// is not a number is a bad row.
+    fn y_values(&self, column: &ArrayRef) -> Result<Vec<Option<f64>>> {
+        let data_type = column.data_type();
+        if data_type.is_numeric() || *data_type == DataType::Boolean {
+            let values = cast(column, &DataType::Float64)?;
+            return Ok(values.as_primitive::<Float64Type>().iter().collect());
+        }
+        if !matches!(data_type, DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View | DataType::Dictionary(..)) {
+            return Err(anyhow!("Column '{}' has type {}, which cannot be plotted as y", self.y_name, data_type));
+        }
+
+        let text = cast(column, &DataType::Utf8)?;
+        let mut values = Vec::with_capacity(text.len());
+        for (row, field) in text.as_string::<i32>().iter().enumerate() {
+            let field = field.unwrap_or_default();
+            if input::is_missing(field) {
+                values.push(None);
+                continue;
+            }
+            match field.trim().parse() {
+                Ok(value) => values.push(Some(value)),
+                Err(_) => {
+                    self.options.on_bad_row.handle(anyhow!(
+                        "Invalid number '{}' in {}, row {}, column '{}'",
+                        field,
+                        input::display_name(self.file_path),
+                        self.rows + row + 1,
+                        self.y_name
+                    ))?;
+                    values.push(None);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    fn finish(self) -> Result<Dataset> {
+        let is_time = self.is_time && !self.collector.is_categorical();
+        Ok(self.collector.finish(self.x_name, self.y_name).with_time(is_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataPoint;
+    use arrow_array::{Float64Array, Int32Array, StringArray, TimestampMillisecondArray};
+    use std::sync::Arc;
+
+    fn write(name: &str, batch: &RecordBatch) -> String {
+        let path = std::env::temp_dir().join(format!("fastplot-{}-{}", std::process::id(), name));
+        let file = File::create(&path).unwrap();
+        if name.ends_with(".parquet") {
+            let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+            writer.write(batch).unwrap();
+            writer.close().unwrap();
+        } else {
+            let mut writer = arrow_ipc::writer::FileWriter::try_new(file, &batch.schema()).unwrap();
+            writer.write(batch).unwrap();
+            writer.finish().unwrap();
+        }
+        path.to_string_lossy().into_owned()
+    }
+
+    fn batch() -> RecordBatch {
+        RecordBatch::try_from_iter([
+            ("region", Arc::new(StringArray::from(vec!["north", "south", "east"])) as ArrayRef),
+            ("ts", Arc::new(TimestampMillisecondArray::from(vec![1_709_287_200_000, 1_709_287_260_000, 1_709_287_320_000])) as ArrayRef),
+            ("latency", Arc::new(Float64Array::from(vec![Some(12.5), None, Some(9.0)])) as ArrayRef),
+            ("count", Arc::new(Int32Array::from(vec![3, 5, 4])) as ArrayRef),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn test_parquet_timestamps_and_selected_columns() {
+        let path = write("metrics.parquet", &batch());
+        let options = ReadOptions::new().with_x(Some("ts".into())).with_y(Some("latency".into()));
+        let dataset = parse_columnar(&path, &options).unwrap();
+        assert!(dataset.is_time && !dataset.is_categorical);
+        assert_eq!(dataset.points[0].x_numeric(), Some(1_709_287_200.0));
+        assert!(dataset.points[1].is_gap());
+        assert_eq!(dataset.points[2].y(), 9.0);
+
+        let error = parse_columnar(&path, &ReadOptions::new().with_y(Some("latncy".into()))).unwrap_err();
+        assert_eq!(error.to_string(), format!("Column 'latncy' not found in {}; columns are: region, ts, latency, count", path));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_arrow_strings_are_categories() {
+        let path = write("regions.arrow", &batch());
+        let options = ReadOptions::new().with_y(Some("count".into()));
+        let dataset = parse_columnar(&path, &options).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(dataset.is_categorical && !dataset.is_time);
+        assert_eq!(dataset.categories, vec!["north", "south", "east"]);
+        assert!(matches!(&dataset.points[1], DataPoint::Categorical(region, y) if region == "south" && *y == 5.0));
+    }
+}
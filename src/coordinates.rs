@@ -1,6 +1,10 @@
 use crate::data::DataPoint;
 use std::collections::HashMap;
 
+/// Terminal cells are roughly twice as tall as they are wide, so horizontal
+/// distances are stretched by this factor to keep circles round
+pub const CELL_ASPECT: f64 = 2.0;
+
 /// Simple coordinate pair for numeric positioning
 /// This is used internally for coordinate transformations after type resolution
 #[derive(Debug, Clone, Copy)]
@@ -144,7 +148,7 @@ impl CoordinateTransformer {
         let norm_x = (point.x - self.data_bounds.min_x) / x_range;
         let norm_y = (point.y - self.data_bounds.min_y) / y_range;
 
-        if norm_x < 0.0 || norm_x > 1.0 || norm_y < 0.0 || norm_y > 1.0 {
+        if !(0.0..=1.0).contains(&norm_x) || !(0.0..=1.0).contains(&norm_y) {
             return None;
         }
 
@@ -182,6 +186,89 @@ impl CoordinateTransformer {
     }
 }
 
+/// Maps polar (angle, radius) data onto a circle centred in the plot area.
+/// Angles are in radians, counter-clockwise from the positive x axis, and
+/// negative radii are reflected through the centre.
+pub struct PolarTransformer {
+    max_radius: f64,
+    center_col: f64,
+    center_row: f64,
+    radius_rows: f64,
+    screen_width: usize,
+    screen_height: usize,
+}
+
+impl PolarTransformer {
+    pub fn new(max_radius: f64, width: usize, height: usize, margins: Margins) -> Self {
+        let plot_width = width.saturating_sub(margins.left + margins.right);
+        let plot_height = height.saturating_sub(margins.top + margins.bottom);
+
+        // Largest circle that fits the plot area once columns are stretched
+        let radius_rows = ((plot_height as f64 - 1.0) / 2.0)
+            .min((plot_width as f64 - 1.0) / 2.0 / CELL_ASPECT)
+            .floor()
+            .max(1.0);
+
+        Self {
+            max_radius,
+            center_col: (margins.left + plot_width / 2) as f64,
+            center_row: (margins.top + plot_height / 2) as f64,
+            radius_rows,
+            screen_width: width,
+            screen_height: height,
+        }
+    }
+
+    /// Screen position, possibly fractional, of an angle at a fraction of the
+    /// outer radius; fractions above 1.0 land outside the plotted circle
+    pub fn position(&self, theta: f64, radius_fraction: f64) -> (f64, f64) {
+        let col = self.center_col + theta.cos() * radius_fraction * self.radius_rows * CELL_ASPECT;
+        let row = self.center_row - theta.sin() * radius_fraction * self.radius_rows;
+        (col, row)
+    }
+
+    pub fn polar_to_screen(&self, theta: f64, r: f64) -> Option<ScreenPoint> {
+        if !theta.is_finite() || !r.is_finite() || self.max_radius <= 0.0 {
+            return None;
+        }
+
+        let fraction = r / self.max_radius;
+        if fraction.abs() > 1.0 + 1e-9 {
+            return None;
+        }
+
+        let (col, row) = self.position(theta, fraction);
+        self.to_screen_point(col, row)
+    }
+
+    /// Round a fractional position to a cell, rejecting positions off the canvas
+    pub fn to_screen_point(&self, col: f64, row: f64) -> Option<ScreenPoint> {
+        let (col, row) = (col.round(), row.round());
+        if col < 0.0 || row < 0.0 {
+            return None;
+        }
+
+        let (col, row) = (col as usize, row as usize);
+        if col < self.screen_width && row < self.screen_height {
+            Some(ScreenPoint { col, row })
+        } else {
+            None
+        }
+    }
+
+    pub fn center(&self) -> ScreenPoint {
+        ScreenPoint {
+            col: self.center_col as usize,
+            row: self.center_row as usize,
+        }
+    }
+
+    /// Outer radius in rows; the horizontal radius is this times `CELL_ASPECT`
+    pub fn radius_rows(&self) -> f64 {
+        self.radius_rows
+    }
+}
+
 pub struct CategoricalTransformer {
     category_map: HashMap<String, f64>,
     data_bounds: DataBounds,
@@ -316,4 +403,23 @@ mod tests {
         let in_bounds = NumericCoordinate::new(5.0, 5.0);
         assert!(transformer.data_to_screen(in_bounds).is_some());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_polar_transform_accounts_for_cell_aspect() {
+        let margins = Margins { left: 0, right: 0, top: 0, bottom: 0 };
+        let transformer = PolarTransformer::new(2.0, 81, 21, margins);
+        let center = transformer.center();
+        assert_eq!(center, ScreenPoint { col: 40, row: 10 });
+
+        // The full radius spans twice as many columns as rows
+        let east = transformer.polar_to_screen(0.0, 2.0).unwrap();
+        let north = transformer.polar_to_screen(std::f64::consts::FRAC_PI_2, 2.0).unwrap();
+        assert_eq!(east, ScreenPoint { col: 60, row: 10 });
+        assert_eq!(north, ScreenPoint { col: 40, row: 0 });
+
+        // Negative radii point the opposite way; beyond the outer ring is clipped
+        let west = transformer.polar_to_screen(0.0, -1.0).unwrap();
+        assert_eq!(west, ScreenPoint { col: 30, row: 10 });
+        assert!(transformer.polar_to_screen(0.0, 2.5).is_none());
+    }
+}
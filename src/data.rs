@@ -1,8 +1,11 @@
 use anyhow::{Result, anyhow};
-use csv::ReaderBuilder;
-use std::fs::File;
-use crate::function;
+use std::io::Read;
+use std::ops::Range;
+use crate::{analysis, calculus, delimited, downsample, function, implicit, input, json, ode, pattern, timestamp};
+use crate::input::InputFormat;
 
+/// A single observation. A numeric point with a non-finite y is a gap marker:
+/// it is never drawn, and lines are not joined across it.
 #[derive(Debug, Clone)]
 pub enum DataPoint {
     Numeric(f64, f64),
@@ -10,6 +13,15 @@ pub enum DataPoint {
 }
 
 impl DataPoint {
+    /// Gap marker placed at `x`, breaking a line into separate segments
+    pub fn gap(x: f64) -> Self {
+        DataPoint::Numeric(x, f64::NAN)
+    }
+
+    pub fn is_gap(&self) -> bool {
+        !self.y().is_finite()
+    }
+
     pub fn y(&self) -> f64 {
         match self {
             DataPoint::Numeric(_, y) => *y,
@@ -51,13 +63,34 @@ impl From<LegacyDataPoint> for DataPoint {
     }
 }
 
+/// A named run of points within a dataset, drawn as its own line with a legend entry
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub label: String,
+    /// Indices of this series' points in `Dataset::points`
+    pub range: Range<usize>,
+}
+
 #[derive(Debug)]
 pub struct Dataset {
+    /// All points, with multiple series stored one after another
     pub points: Vec<DataPoint>,
     pub x_label: String,
     pub y_label: String,
     pub is_categorical: bool,
     pub categories: Vec<String>,
+    /// Series boundaries; empty when the dataset is a single unnamed series
+    pub series: Vec<Series>,
+    /// Points are already in drawing order (e.g. parametric curves) and must not be sorted by x
+    pub preserve_order: bool,
+    /// Points are (angle in radians, radius) pairs to be drawn on polar axes
+    pub is_polar: bool,
+    /// Roots, extrema and intersections to mark on the plot
+    pub features: Vec<analysis::Feature>,
+    /// Functions searched for features, kept to intersect with other sources
+    pub analyzed: Option<analysis::Analyzed>,
+    /// x values are epoch seconds, labelled on the axis as dates and times
+    pub is_time: bool,
 }
 
 impl Dataset {
@@ -68,6 +101,12 @@ impl Dataset {
             y_label,
             is_categorical: false,
             categories: Vec::new(),
+            series: Vec::new(),
+            preserve_order: false,
+            is_polar: false,
+            features: Vec::new(),
+            analyzed: None,
+            is_time: false,
         }
     }
     
@@ -78,38 +117,469 @@ impl Dataset {
             y_label,
             is_categorical: true,
             categories,
+            series: Vec::new(),
+            preserve_order: false,
+            is_polar: false,
+            features: Vec::new(),
+            analyzed: None,
+            is_time: false,
+        }
+    }
+
+    /// Builder for labelling a single-series dataset, e.g. with its function expression
+    pub fn with_series_label(mut self, label: &str) -> Self {
+        self.series = vec![Series {
+            label: label.to_string(),
+            range: 0..self.points.len(),
+        }];
+        self
+    }
+
+    /// Builder for marking points as already being in drawing order
+    pub fn with_preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Builder for marking points as polar (angle, radius) pairs
+    pub fn with_polar(mut self, is_polar: bool) -> Self {
+        self.is_polar = is_polar;
+        self
+    }
+
+    /// Builder for marking x values as timestamps in epoch seconds
+    pub fn with_time(mut self, is_time: bool) -> Self {
+        self.is_time = is_time;
+        self
+    }
+
+    /// Builder for attaching analysed points to mark on the plot
+    pub fn with_features(mut self, features: Vec<analysis::Feature>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Reinterpret x as an angle in degrees and y as a radius, converting the
+    /// angles to radians. Points keep their file order, as polar patterns
+    /// are usually listed around the circle.
+    pub fn into_polar_from_degrees(mut self) -> Result<Self> {
+        if self.is_categorical {
+            return Err(anyhow!("Polar plots need numeric angle and radius columns"));
+        }
+        if !self.is_polar {
+            for point in &mut self.points {
+                if let DataPoint::Numeric(angle, _) = point {
+                    *angle = angle.to_radians();
+                }
+            }
+        }
+        Ok(self.with_preserve_order(true).with_polar(true))
+    }
+
+    /// Each series as a label and its points; unnamed datasets use the y label
+    pub fn series_slices(&self) -> Vec<(&str, &[DataPoint])> {
+        if self.series.is_empty() {
+            return vec![(self.y_label.as_str(), &self.points[..])];
+        }
+
+        self.series.iter()
+            .map(|series| (series.label.as_str(), &self.points[series.range.clone()]))
+            .collect()
+    }
+
+    /// Merge several numeric datasets into one multi-series dataset on shared axes
+    pub fn combine(datasets: Vec<Dataset>, y_label: &str) -> Result<Dataset> {
+        if datasets.iter().any(|d| d.is_categorical) {
+            return Err(anyhow!("Multiple series can only be combined from numeric data"));
+        }
+
+        let x_label = datasets.first()
+            .map(|d| d.x_label.clone())
+            .unwrap_or_else(|| "x".to_string());
+
+        // When any input is in drawing order the result must be too, so the
+        // other inputs are sorted by x up front instead of at render time
+        let preserve_order = datasets.iter().any(|d| d.preserve_order);
+        let is_polar = datasets.iter().any(|d| d.is_polar);
+        let is_time = !datasets.is_empty() && datasets.iter().all(|d| d.is_time);
+        if is_polar && datasets.iter().any(|d| !d.is_polar) {
+            return Err(anyhow!("Cannot overlay polar and cartesian series"));
         }
+
+        let mut points = Vec::new();
+        let mut series = Vec::new();
+        for dataset in &datasets {
+            for (label, slice) in dataset.series_slices() {
+                let start = points.len();
+                points.extend_from_slice(slice);
+                if preserve_order && !dataset.preserve_order {
+                    points[start..].sort_by(|a, b| {
+                        let a_x = a.x_numeric().unwrap_or(0.0);
+                        let b_x = b.x_numeric().unwrap_or(0.0);
+                        a_x.partial_cmp(&b_x).unwrap()
+                    });
+                }
+                series.push(Series {
+                    label: label.to_string(),
+                    range: start..points.len(),
+                });
+            }
+        }
+
+        // Tags are renumbered so that features from different sources stay unique
+        let features = analysis::tag_features(datasets.iter().flat_map(|d| d.features.clone()).collect());
+
+        let mut combined = Dataset::new_numeric(points, x_label, y_label.to_string())
+            .with_preserve_order(preserve_order)
+            .with_polar(is_polar)
+            .with_time(is_time)
+            .with_features(features);
+        combined.series = series;
+        Ok(combined)
     }
 }
 
-/// Parse data source which can be either a CSV file path or a function expression
-pub fn parse_data_source(source: &str, range: Option<&str>, points: Option<usize>) -> Result<Dataset> {
+/// Settings that control how a data source is read. Expression sources use
+/// the range and sampling settings; file sources ignore them.
+#[derive(Debug, Clone, Default)]
+pub struct SourceOptions {
+    /// Range for expressions as "min:max" (x, t or theta depending on the source)
+    pub range: Option<String>,
+    /// Number of samples for expressions, or the evaluation budget when adaptive
+    pub points: Option<usize>,
+    /// Refine `function:` samples to the resolution of the final plot
+    pub adaptive: Option<function::AdaptiveSampling>,
+    /// Expression parameters as "name=value" or sweeps as "name=start..end:count"
+    pub params: Vec<String>,
+    /// Add the numerical derivative of each `function:` expression as a series
+    pub derivative: bool,
+    /// Add the cumulative integral of each `function:` expression as a series
+    pub integral: bool,
+    /// Find roots, extrema and intersections of `function:` expressions
+    pub features: bool,
+    /// How file and stdin sources are read
+    pub input: input::ReadOptions,
+}
+
+impl SourceOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_range(mut self, range: Option<String>) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn with_points(mut self, points: Option<usize>) -> Self {
+        self.points = points;
+        self
+    }
+
+    pub fn with_adaptive(mut self, adaptive: Option<function::AdaptiveSampling>) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    pub fn with_params(mut self, params: Vec<String>) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn with_derivative(mut self, derivative: bool) -> Self {
+        self.derivative = derivative;
+        self
+    }
+
+    pub fn with_integral(mut self, integral: bool) -> Self {
+        self.integral = integral;
+        self
+    }
+
+    pub fn with_features(mut self, features: bool) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn with_input(mut self, input: input::ReadOptions) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Parse the parameters into one binding set per curve to draw
+    fn parameter_sets(&self) -> Result<Vec<function::ParameterSet>> {
+        let parameters = self.params.iter()
+            .map(|text| function::Parameter::parse(text))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(function::parameter_sets(&parameters))
+    }
+}
+
+/// Parse data source which can be a CSV, JSON, Parquet or Arrow file path, "-" for stdin, a
+/// SQLite query, or a function, polar or parametric expression
+pub fn parse_data_source(source: &str, options: &SourceOptions) -> Result<Dataset> {
     if source.starts_with("function:") {
         // Parse function expression
         let expression = source.strip_prefix("function:").unwrap();
-        parse_function_expression(expression, range, points)
+        parse_function_expression(expression, options)
+    } else if let Some(expression) = source.strip_prefix("polar:") {
+        // Parse polar r(theta) expression
+        parse_polar_expression(expression, options)
+    } else if let Some(expressions) = source.strip_prefix("param:") {
+        // Parse parametric x(t);y(t) expressions
+        parse_parametric_expression(expressions, options)
+    } else if let Some(equations) = source.strip_prefix("implicit:") {
+        // Parse implicit F(x, y) = 0 equations
+        parse_implicit_expression(equations, options)
+    } else if let Some(system) = source.strip_prefix("ode:") {
+        // Solve y' = f(x, y) initial value problems numerically
+        parse_ode_expression(system, options)
+    } else if let Some(spec) = source.strip_prefix("sqlite:") {
+        // Run a query against a local SQLite database
+        parse_sqlite(spec, &options.input)
     } else {
-        // Parse as CSV file
-        parse_csv(source)
+        // Parse as a data file in CSV, JSON or another supported format
+        parse_file(source, &options.input)
     }
 }
 
-/// Parse function expression and generate dataset
-pub fn parse_function_expression(expression: &str, range: Option<&str>, points: Option<usize>) -> Result<Dataset> {
-    let func = function::Function::new(expression);
+#[cfg(feature = "sqlite")]
+fn parse_sqlite(spec: &str, options: &input::ReadOptions) -> Result<Dataset> {
+    crate::sqlite::parse_sqlite(spec, options)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn parse_sqlite(spec: &str, _options: &input::ReadOptions) -> Result<Dataset> {
+    let database = spec.split_once('?').map_or(spec, |(path, _)| path);
+    Err(anyhow!(
+        "Querying {} needs SQLite support; build with `cargo install --path . --features sqlite`",
+        database
+    ))
+}
+
+/// Prefixes of the sources that are computed or queried rather than read as a file
+const GENERATED_SOURCES: [&str; 6] = ["function:", "polar:", "param:", "implicit:", "ode:", "sqlite:"];
+
+/// Whether a source is a file path or stdin, as opposed to an expression or query
+pub fn is_file_source(source: &str) -> bool {
+    !GENERATED_SOURCES.iter().any(|prefix| source.starts_with(prefix))
+}
+
+/// Parse function expression and generate dataset.
+/// Several expressions separated by `;` are evaluated over the same range
+/// and returned as one series each, followed by their derivatives and
+/// integrals when requested.
+pub fn parse_function_expression(expression: &str, options: &SourceOptions) -> Result<Dataset> {
+    let expressions: Vec<&str> = expression.split(';')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .collect();
+    if expressions.is_empty() {
+        return Err(anyhow!("No function expression given"));
+    }
     
     // Determine range
-    let (x_min, x_max) = if let Some(range_str) = range {
+    let (x_min, x_max) = if let Some(range_str) = &options.range {
         parse_range(range_str)?
     } else {
         function::detect_range(expression)
     };
     
-    // Generate dataset (functions always produce numeric data)
-    let dataset = func.generate_dataset(x_min, x_max, points)?;
+    // Generate one dataset per expression and parameter set (functions always produce numeric data)
+    let parameter_sets = options.parameter_sets()?;
+    let mut datasets = Vec::new();
+    let mut analyzed = Vec::new();
+    for expr in &expressions {
+        for set in &parameter_sets {
+            // d/dx(...) wrappers are sampled as the inner expression, then differentiated
+            let (order, inner) = strip_derivatives(expr);
+            let func = function::Function::new(inner).with_params(&set.bindings);
+            let mut dataset = match &options.adaptive {
+                Some(sampling) => func.generate_adaptive_dataset(x_min, x_max, options.points, sampling)?,
+                None => func.generate_dataset(x_min, x_max, options.points)?,
+            };
+            for _ in 0..order {
+                dataset = derived_dataset(calculus::derivative(&dataset.points), expr);
+            }
+
+            let derivative = options.derivative.then(|| calculus::derivative(&dataset.points));
+            let integral = options.integral.then(|| calculus::integral(&dataset.points));
+            datasets.push(dataset.with_series_label(&set.series_label(expr)));
+            // Only plain expressions can be refined; d/dx(...) series exist just as samples
+            if options.features && order == 0 {
+                analyzed.push((set.series_label(expr), func));
+            }
+            if let Some(points) = derivative {
+                datasets.push(derived_dataset(points, &set.series_label(&format!("d/dx({})", expr))));
+            }
+            if let Some(points) = integral {
+                datasets.push(derived_dataset(points, &set.series_label(&format!("∫{} dx", expr))));
+            }
+        }
+    }
     
-    // Functions already return the new Dataset format
-    Ok(dataset)
+    let dataset = if datasets.len() == 1 {
+        datasets.remove(0)
+    } else if expressions.len() == 1 && !options.derivative && !options.integral {
+        // A family of one expression keeps its f(x) label
+        let y_label = datasets[0].y_label.clone();
+        Dataset::combine(datasets, &y_label)?
+    } else {
+        Dataset::combine(datasets, "y")?
+    };
+
+    if options.features {
+        let features = analysis::analyze(&analyzed, x_min, x_max, options.points.unwrap_or(200));
+        let mut dataset = dataset.with_features(features);
+        dataset.analyzed = Some(analysis::Analyzed { functions: analyzed, x_min, x_max });
+        Ok(dataset)
+    } else {
+        Ok(dataset)
+    }
+}
+
+/// Split `d/dx(...)` wrappers off an expression, returning how many were removed
+fn strip_derivatives(expression: &str) -> (usize, &str) {
+    let mut order = 0;
+    let mut inner = expression.trim();
+    while let Some(body) = inner.strip_prefix("d/dx(").and_then(|rest| rest.strip_suffix(')')) {
+        // Only strip when the parentheses enclose the whole expression,
+        // not e.g. "d/dx(x^2) + d/dx(x)"
+        let mut depth = 0;
+        let balanced = body.chars().all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth >= 0
+        });
+        if !balanced || depth != 0 {
+            break;
+        }
+        order += 1;
+        inner = body.trim();
+    }
+    (order, inner)
+}
+
+/// Wrap computed points (a derivative or integral) as a labelled series
+fn derived_dataset(points: Vec<DataPoint>, label: &str) -> Dataset {
+    Dataset::new_numeric(points, "x".to_string(), label.to_string()).with_series_label(label)
+}
+
+/// Parse parametric expressions "x(t);y(t)" and trace the curve over the t range,
+/// which defaults to one full turn (0 to 2π)
+pub fn parse_parametric_expression(expressions: &str, options: &SourceOptions) -> Result<Dataset> {
+    let parts: Vec<&str> = expressions.split(';').map(str::trim).collect();
+    if parts.len() != 2 || parts.iter().any(|p| p.is_empty()) {
+        return Err(anyhow!("Parametric source must be 'param:x(t);y(t)', got: {}", expressions));
+    }
+    
+    let (t_min, t_max) = if let Some(range_str) = &options.range {
+        parse_range(range_str)?
+    } else {
+        (0.0, 2.0 * std::f64::consts::PI)
+    };
+    
+    let mut datasets = Vec::new();
+    for set in options.parameter_sets()? {
+        let curve = function::ParametricCurve::new(parts[0], parts[1]).with_params(&set.bindings);
+        let dataset = curve.generate_dataset(t_min, t_max, options.points)?;
+        let label = set.series_label(&format!("({}, {})", parts[0], parts[1]));
+        datasets.push(dataset.with_series_label(&label));
+    }
+    
+    if datasets.len() == 1 {
+        Ok(datasets.remove(0))
+    } else {
+        let y_label = datasets[0].y_label.clone();
+        Dataset::combine(datasets, &y_label)
+    }
+}
+
+/// Parse polar expressions "r(theta)", separated by ';' for several curves,
+/// over a theta range that defaults to one full turn (0 to 2π)
+pub fn parse_polar_expression(expression: &str, options: &SourceOptions) -> Result<Dataset> {
+    let (theta_min, theta_max) = if let Some(range_str) = &options.range {
+        parse_range(range_str)?
+    } else {
+        (0.0, 2.0 * std::f64::consts::PI)
+    };
+    
+    let parameter_sets = options.parameter_sets()?;
+    let mut datasets = Vec::new();
+    for expr in expression.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        for set in &parameter_sets {
+            let curve = function::PolarCurve::new(expr).with_params(&set.bindings);
+            let dataset = curve.generate_dataset(theta_min, theta_max, options.points)?;
+            datasets.push(dataset.with_series_label(&set.series_label(expr)));
+        }
+    }
+    
+    match datasets.len() {
+        0 => Err(anyhow!("Polar source must be 'polar:r(theta)', got: {}", expression)),
+        1 => Ok(datasets.into_iter().next().unwrap()),
+        _ => Dataset::combine(datasets, "r(theta)"),
+    }
+}
+
+/// Parse implicit equations such as "x^2 + y^2 = 1" and trace each zero
+/// contour. The range applies to both x and y so that the grid is square in
+/// data units; `points` sets the number of grid cells along each axis.
+pub fn parse_implicit_expression(equations: &str, options: &SourceOptions) -> Result<Dataset> {
+    let range = if let Some(range_str) = &options.range {
+        parse_range(range_str)?
+    } else {
+        function::detect_range(equations)
+    };
+    let cells = options.points.unwrap_or(200);
+    
+    let parameter_sets = options.parameter_sets()?;
+    let mut datasets = Vec::new();
+    for equation in equations.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        for set in &parameter_sets {
+            let curve = implicit::ImplicitCurve::new(equation)?.with_params(&set.bindings);
+            let dataset = curve.generate_dataset(range, range, cells)?;
+            datasets.push(dataset.with_series_label(&set.series_label(equation)));
+        }
+    }
+    
+    match datasets.len() {
+        0 => Err(anyhow!("Implicit source must be 'implicit:F(x, y) = 0', got: {}", equations)),
+        1 => Ok(datasets.into_iter().next().unwrap()),
+        _ => Dataset::combine(datasets, "y"),
+    }
+}
+
+/// Solve an initial value problem such as "y' = -0.5*y + sin(x); y(0) = 1",
+/// one series per state variable. RK4 takes `points` steps across the range,
+/// which defaults to ten units from the initial condition; `--adaptive`
+/// switches to RK45 with `points` as the step budget.
+pub fn parse_ode_expression(system: &str, options: &SourceOptions) -> Result<Dataset> {
+    let system = ode::OdeSystem::parse(system)?;
+    let (x_min, x_max) = if let Some(range_str) = &options.range {
+        parse_range(range_str)?
+    } else {
+        system.default_range()
+    };
+    let points = options.points.unwrap_or(200);
+    
+    let parameter_sets = options.parameter_sets()?;
+    let mut datasets = Vec::new();
+    for set in &parameter_sets {
+        let solved = system.clone().with_params(&set.bindings)
+            .generate_datasets(x_min, x_max, points, options.adaptive.is_some())?;
+        for (name, dataset) in system.names().iter().zip(solved) {
+            datasets.push(dataset.with_series_label(&set.series_label(name)));
+        }
+    }
+    
+    if datasets.len() == 1 {
+        Ok(datasets.remove(0))
+    } else {
+        Dataset::combine(datasets, "y")
+    }
 }
 
 /// Parse range string in format "min:max"
@@ -131,67 +601,301 @@ fn parse_range(range_str: &str) -> Result<(f64, f64)> {
     Ok((min, max))
 }
 
-pub fn parse_csv(file_path: &str) -> Result<Dataset> {
-    let file = File::open(file_path)?;
-    let mut reader = ReaderBuilder::new().has_headers(true).from_reader(file);
-    
-    // Get headers for axis labels
-    let headers = reader.headers()?.clone();
-    let x_label = headers.get(0).unwrap_or("x").to_string();
-    let y_label = headers.get(1).unwrap_or("y").to_string();
-    
-    let mut points = Vec::new();
-    let mut is_categorical = false;
-    let mut categories = Vec::new();
-    
-    for result in reader.records() {
-        let record = result?;
-        let x_str = record.get(0).unwrap_or("0").trim();
-        let y: f64 = record.get(1).unwrap_or("0").trim().parse()?;
-        
-        // Try to parse x as numeric first
-        if let Ok(x_numeric) = x_str.parse::<f64>() {
-            // If we haven't seen categorical data yet, continue with numeric
-            if !is_categorical {
-                points.push(DataPoint::Numeric(x_numeric, y));
-            } else {
-                // Mix of categorical and numeric - treat as categorical
-                let category = x_str.to_string();
-                if !categories.contains(&category) {
-                    categories.push(category.clone());
+/// Collects rows read from a file into points. x values are numeric until one
+/// turns up that is not a number, when the points so far become categories.
+/// Missing values leave gaps in numeric data.
+#[derive(Default)]
+pub struct PointCollector {
+    points: Vec<DataPoint>,
+    categories: Vec<String>,
+    is_categorical: bool,
+    /// Previous numeric x, where a row without an x breaks the line
+    last_x: Option<f64>,
+    /// Buckets to aggregate numeric points into once there are too many to keep
+    downsample: Option<usize>,
+    /// Takes over from `points` when downsampling starts
+    m4: Option<downsample::M4>,
+}
+
+impl PointCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder for keeping memory bounded on large inputs: past four points
+    /// per bucket, numeric points are aggregated into `buckets` M4 buckets
+    pub fn with_downsample(mut self, buckets: Option<usize>) -> Self {
+        self.downsample = buckets;
+        self
+    }
+
+    fn push_numeric(&mut self, point: DataPoint) {
+        if let Some(m4) = &mut self.m4 {
+            match point {
+                DataPoint::Numeric(x, y) if y.is_finite() => m4.push(x, y),
+                DataPoint::Numeric(x, _) => m4.push_gap(x),
+                DataPoint::Categorical(..) => {}
+            }
+            return;
+        }
+        self.points.push(point);
+
+        // Below four points per bucket, M4 would keep as many as there are
+        if let Some(buckets) = self.downsample.filter(|b| self.points.len() > b * 4) {
+            let mut m4 = downsample::M4::new(buckets);
+            for point in self.points.drain(..) {
+                if let DataPoint::Numeric(x, y) = point {
+                    if y.is_finite() { m4.push(x, y) } else { m4.push_gap(x) }
                 }
-                points.push(DataPoint::Categorical(category, y));
             }
-        } else {
-            // X is categorical
-            if !is_categorical {
-                // First categorical value found - convert previous numeric points to categorical
-                is_categorical = true;
-                let old_points = std::mem::take(&mut points);
-                for old_point in old_points {
-                    if let DataPoint::Numeric(x, y) = old_point {
-                        let category = x.to_string();
-                        if !categories.contains(&category) {
-                            categories.push(category.clone());
+            self.m4 = Some(m4);
+        }
+    }
+
+    /// Numeric points so far, from the aggregate once downsampling has begun
+    fn take_points(&mut self) -> Vec<DataPoint> {
+        match self.m4.take() {
+            Some(m4) => m4.into_points(),
+            None => std::mem::take(&mut self.points),
+        }
+    }
+
+    /// Add a row given the text of its x, x as a number when it is one, and y,
+    /// which is `None` when missing or unreadable
+    pub fn push(&mut self, x_text: &str, x: Option<f64>, y: Option<f64>) {
+        if input::is_missing(x_text) {
+            if let (Some(x), false) = (self.last_x, self.is_categorical) {
+                self.push_numeric(DataPoint::gap(x));
+            }
+            return;
+        }
+        let Some(y) = y else {
+            // Categories have no line to break, so the row is simply dropped
+            if let (Some(x), false) = (x, self.is_categorical) {
+                self.push_numeric(DataPoint::gap(x));
+                self.last_x = Some(x);
+            }
+            return;
+        };
+
+        match x {
+            Some(x) if !self.is_categorical => {
+                self.push_numeric(DataPoint::Numeric(x, y));
+                self.last_x = Some(x);
+            }
+            // Mix of categorical and numeric - treat as categorical
+            Some(_) => self.push_category(x_text, y),
+            None => {
+                if !self.is_categorical {
+                    // First categorical value found - convert previous numeric points to categorical
+                    self.is_categorical = true;
+                    let old_points = self.take_points();
+                    for old_point in old_points.into_iter().filter(|p| !p.is_gap()) {
+                        if let DataPoint::Numeric(x, y) = old_point {
+                            self.push_category(&x.to_string(), y);
                         }
-                        points.push(DataPoint::Categorical(category, y));
                     }
                 }
+                self.push_category(x_text, y);
             }
-            
-            let category = x_str.to_string();
-            if !categories.contains(&category) {
-                categories.push(category.clone());
+        }
+    }
+
+    fn push_category(&mut self, category: &str, y: f64) {
+        if !self.categories.iter().any(|c| c == category) {
+            self.categories.push(category.to_string());
+        }
+        self.points.push(DataPoint::Categorical(category.to_string(), y));
+    }
+
+    pub fn is_categorical(&self) -> bool {
+        self.is_categorical
+    }
+
+    pub fn finish(mut self, x_label: String, y_label: String) -> Dataset {
+        if self.is_categorical {
+            Dataset::new_categorical(self.points, x_label, y_label, self.categories)
+        } else {
+            Dataset::new_numeric(self.take_points(), x_label, y_label)
+        }
+    }
+}
+
+/// Read a file or stdin source in the format its extension or `--format` names,
+/// or line by line with `--regex`
+pub fn parse_file(file_path: &str, options: &input::ReadOptions) -> Result<Dataset> {
+    if let Some(regex) = &options.regex {
+        return pattern::parse_pattern(file_path, regex, options);
+    }
+    match options.format_for(file_path) {
+        InputFormat::Delimited => parse_csv(file_path, options),
+        InputFormat::Json | InputFormat::Ndjson => json::parse_json(file_path, options),
+        #[cfg(feature = "columnar")]
+        InputFormat::Parquet | InputFormat::Arrow => crate::columnar::parse_columnar(file_path, options),
+        #[cfg(not(feature = "columnar"))]
+        InputFormat::Parquet | InputFormat::Arrow => Err(anyhow!(
+            "Reading {} needs Parquet and Arrow support; build with `cargo install --path . --features columnar`",
+            input::display_name(file_path)
+        )),
+    }
+}
+
+pub fn parse_csv(file_path: &str, options: &input::ReadOptions) -> Result<Dataset> {
+    read_csv(input::open_input(file_path)?, file_path, options)
+}
+
+/// Read delimited text from `input`, naming it `file_path` in messages
+pub fn read_csv(input: Box<dyn Read>, file_path: &str, options: &input::ReadOptions) -> Result<Dataset> {
+    let table = delimited::read_table(input, options)?;
+
+    // Columns chosen by name, or the first two
+    let x_column = column_index(&table.headers, options.x.as_deref(), 0, file_path)?;
+    let y_column = column_index(&table.headers, options.y.as_deref(), 1, file_path)?;
+
+    // Get headers for axis labels
+    let x_label = table.headers.get(x_column).map_or("x", String::as_str).to_string();
+    let y_label = table.headers.get(y_column).map_or("y", String::as_str).to_string();
+
+    let mut collector = PointCollector::new().with_downsample(options.downsample);
+    for result in table.records {
+        let mut record = result?;
+        record.line += options.row_offset;
+        let x_str = record.fields.get(x_column).map_or("", String::as_str);
+
+        // Missing values, and unreadable ones the policy lets through, leave a gap
+        let y = match record.number(y_column, file_path, &table.headers) {
+            Ok(y) => y,
+            Err(error) => {
+                options.on_bad_row.handle(error)?;
+                None
             }
-            points.push(DataPoint::Categorical(category, y));
+        };
+        collector.push(x_str, x_str.parse().ok(), y);
+    }
+
+    Ok(collector.finish(x_label, y_label))
+}
+
+/// Position of the column named `name`, or `default` when no name is given
+fn column_index(headers: &[String], name: Option<&str>, default: usize, file_path: &str) -> Result<usize> {
+    let Some(name) = name else {
+        return Ok(default);
+    };
+    headers.iter().position(|header| header == name).ok_or_else(|| {
+        anyhow!(
+            "Column '{}' not found in {}; columns are: {}",
+            name,
+            input::display_name(file_path),
+            headers.join(", ")
+        )
+    })
+}
+
+/// One open-high-low-close record, with its time in epoch seconds
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub time: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<f64>,
+}
+
+impl Candle {
+    /// Whether the period closed at or above its open
+    pub fn is_up(&self) -> bool {
+        self.close >= self.open
+    }
+}
+
+#[derive(Debug)]
+pub struct OhlcDataset {
+    pub candles: Vec<Candle>,
+    pub time_label: String,
+}
+
+/// Parse a CSV with date, open, high, low, close and optional volume columns.
+/// Columns are matched by header name, falling back to that positional order.
+pub fn parse_ohlc(file_path: &str, options: &input::ReadOptions) -> Result<OhlcDataset> {
+    if options.format_for(file_path) != InputFormat::Delimited {
+        return Err(anyhow!("Candlestick charts read OHLC data from delimited text such as CSV"));
+    }
+    let file = input::open_input(file_path)?;
+    let table = delimited::read_table(file, options)?;
+
+    let headers = table.headers;
+    let find_column = |names: &[&str], position: usize| -> Option<usize> {
+        headers.iter()
+            .position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+            .or((position < headers.len()).then_some(position))
+    };
+
+    let time_column = find_column(&["date", "time", "timestamp", "datetime"], 0)
+        .ok_or_else(|| anyhow!("OHLC data needs a date column"))?;
+    let mut price_columns = Vec::new();
+    for (position, name) in ["open", "high", "low", "close"].iter().enumerate() {
+        let column = find_column(&[name], position + 1)
+            .ok_or_else(|| anyhow!("OHLC data needs a '{}' column", name))?;
+        price_columns.push((*name, column));
+    }
+    let volume_column = find_column(&["volume", "vol"], 5);
+
+    let mut candles = Vec::new();
+    for result in table.records {
+        let record = result?;
+        match read_candle(&record, file_path, &headers, time_column, &price_columns, volume_column) {
+            Ok(Some(candle)) => candles.push(candle),
+            // A period with missing prices cannot be drawn
+            Ok(None) => {}
+            Err(error) => options.on_bad_row.handle(error)?,
         }
     }
-    
-    if is_categorical {
-        Ok(Dataset::new_categorical(points, x_label, y_label, categories))
-    } else {
-        Ok(Dataset::new_numeric(points, x_label, y_label))
+
+    candles.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+    Ok(OhlcDataset {
+        candles,
+        time_label: headers.get(time_column).map_or("date", String::as_str).to_string(),
+    })
+}
+
+/// One OHLC row, or `None` when its date or a price is missing
+fn read_candle(
+    record: &delimited::Record,
+    file_path: &str,
+    headers: &[String],
+    time_column: usize,
+    price_columns: &[(&str, usize)],
+    volume_column: Option<usize>,
+) -> Result<Option<Candle>> {
+    let time_str = record.fields.get(time_column).map_or("", String::as_str);
+    if input::is_missing(time_str) {
+        return Ok(None);
     }
+    let time = timestamp::parse_timestamp(time_str).ok_or_else(|| anyhow!(
+        "Invalid date '{}' in {}, line {}, column {}",
+        time_str, input::display_name(file_path), record.line, time_column + 1,
+    ))?;
+
+    let mut prices = [0.0; 4];
+    for (i, (_, column)) in price_columns.iter().enumerate() {
+        match record.number(*column, file_path, headers)? {
+            Some(price) => prices[i] = price,
+            None => return Ok(None),
+        }
+    }
+
+    let volume = volume_column.and_then(|column| record.number(column, file_path, headers).ok().flatten());
+
+    Ok(Some(Candle {
+        time,
+        open: prices[0],
+        high: prices[1],
+        low: prices[2],
+        close: prices[3],
+        volume,
+    }))
 }
 
 /// Reorder categorical dataset according to custom category order
@@ -212,4 +916,190 @@ pub fn reorder_categories(mut dataset: Dataset, custom_order: Vec<String>) -> Re
     dataset.categories = custom_order;
     
     Ok(dataset)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiple_function_expressions() {
+        let options = SourceOptions::new()
+            .with_range(Some("0:1".to_string()))
+            .with_points(Some(11));
+        let dataset = parse_data_source("function:sin(x); cos(x);sin(x)+cos(x)", &options).unwrap();
+
+        let series = dataset.series_slices();
+        let labels: Vec<&str> = series.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec!["sin(x)", "cos(x)", "sin(x)+cos(x)"]);
+        assert!(series.iter().all(|(_, points)| points.len() == 11));
+        assert_eq!(dataset.points.len(), 33);
+
+        // Every series shares the same x samples
+        assert_eq!(series[1].1[0].x_numeric(), Some(0.0));
+        assert_eq!(series[2].1[10].x_numeric(), Some(1.0));
+    }
+
+    #[test]
+    fn test_derivative_and_integral_series() {
+        let options = SourceOptions::new()
+            .with_range(Some("0:2".to_string()))
+            .with_points(Some(21))
+            .with_derivative(true)
+            .with_integral(true);
+        let dataset = parse_data_source("function:x^2", &options).unwrap();
+
+        let series = dataset.series_slices();
+        let labels: Vec<&str> = series.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec!["x^2", "d/dx(x^2)", "∫x^2 dx"]);
+        assert!((series[1].1.last().unwrap().y() - 4.0).abs() < 1e-9);
+        assert!((series[2].1.last().unwrap().y() - 8.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_derivative_syntax() {
+        let options = SourceOptions::new()
+            .with_range(Some("0:1".to_string()))
+            .with_points(Some(11));
+        let dataset = parse_data_source("function:d/dx(d/dx(x^3))", &options).unwrap();
+        assert_eq!(dataset.y_label, "d/dx(d/dx(x^3))");
+        // Second derivative 6x, exact away from the one-sided ends
+        assert!((dataset.points[5].y() - 3.0).abs() < 1e-6);
+
+        assert_eq!(strip_derivatives("d/dx(x^2) + d/dx(x)"), (0, "d/dx(x^2) + d/dx(x)"));
+        assert_eq!(strip_derivatives("d/dx( sin(x) )"), (1, "sin(x)"));
+    }
+
+    #[test]
+    fn test_ode_source_with_parameter_sweep() {
+        let options = SourceOptions::new()
+            .with_range(Some("0:2".to_string()))
+            .with_points(Some(21))
+            .with_params(vec!["k=1..2:2".to_string()]);
+        let dataset = parse_data_source("ode:y' = -k*y; y(0) = 1", &options).unwrap();
+
+        let series = dataset.series_slices();
+        let labels: Vec<&str> = series.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec!["y (k=1)", "y (k=2)"]);
+        let (_, decay) = series[1];
+        assert!((decay.last().unwrap().y() - (-4.0_f64).exp()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_collector_downsamples_large_inputs() {
+        let mut small = PointCollector::new().with_downsample(Some(10));
+        for x in 0..40 {
+            small.push(&x.to_string(), Some(x as f64), Some(1.0));
+        }
+        assert_eq!(small.finish("x".into(), "y".into()).points.len(), 40);
+
+        let mut large = PointCollector::new().with_downsample(Some(10));
+        for x in 0..100_000 {
+            let y = if x == 70_000 { None } else { Some((x % 1_000) as f64) };
+            large.push(&x.to_string(), Some(x as f64), y);
+        }
+        let dataset = large.finish("x".into(), "y".into());
+        assert!(dataset.points.len() <= 10 * 5);
+        assert!(dataset.points.iter().any(|p| p.y() == 999.0));
+        assert!(dataset.points.iter().any(|p| p.is_gap()));
+    }
+
+    #[test]
+    fn test_missing_and_bad_values_leave_gaps() {
+        let path = std::env::temp_dir().join(format!("fastplot-gaps-{}.csv", std::process::id()));
+        std::fs::write(&path, "x,y\n1,1\n2,NA\n3,3\nNaN,4\n5,oops\n6,6\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let error = parse_csv(path, &input::ReadOptions::new()).unwrap_err().to_string();
+        assert!(error.ends_with("line 6, column 2 ('y')"), "{}", error);
+
+        let options = input::ReadOptions::new().with_on_bad_row(input::BadRowPolicy::Skip);
+        let dataset = parse_csv(path, &options).unwrap();
+        let runs: Vec<Vec<f64>> = dataset.points
+            .split(DataPoint::is_gap)
+            .map(|run| run.iter().map(DataPoint::y).collect())
+            .collect();
+        assert_eq!(runs, vec![vec![1.0], vec![3.0], vec![], vec![6.0]]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parameter_sweep_makes_one_series_per_value() {
+        let options = SourceOptions::new()
+            .with_range(Some("0:1".to_string()))
+            .with_points(Some(5))
+            .with_params(vec!["a=1..3:3".to_string(), "b=0.5".to_string()]);
+        let dataset = parse_data_source("function:a*x + b", &options).unwrap();
+
+        let series = dataset.series_slices();
+        let labels: Vec<&str> = series.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, vec!["a*x + b (a=1)", "a*x + b (a=2)", "a*x + b (a=3)"]);
+        assert_eq!(dataset.y_label, "f(x) = a*x + b");
+
+        // Last point of the a=3 curve is 3*1 + 0.5
+        assert_eq!(series[2].1.last().unwrap().y(), 3.5);
+    }
+
+    #[test]
+    fn test_parametric_source() {
+        let dataset = parse_data_source("param:cos(t); sin(t)", &SourceOptions::new().with_points(Some(50))).unwrap();
+        assert!(dataset.preserve_order);
+        assert_eq!(dataset.points.len(), 50);
+        assert_eq!(dataset.series_slices()[0].0, "(cos(t), sin(t))");
+
+        assert!(parse_data_source("param:cos(t)", &SourceOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_combine_sorts_unordered_series_when_mixed() {
+        let unordered = Dataset::new_numeric(
+            vec![DataPoint::Numeric(2.0, 0.0), DataPoint::Numeric(1.0, 0.0)],
+            "x".to_string(),
+            "y".to_string(),
+        );
+        let options = SourceOptions::new()
+            .with_range(Some("0:1".to_string()))
+            .with_points(Some(3));
+        let ordered = parse_data_source("param:t;t", &options).unwrap();
+        let combined = Dataset::combine(vec![unordered, ordered], "y").unwrap();
+
+        assert!(combined.preserve_order);
+        assert_eq!(combined.points[0].x_numeric(), Some(1.0));
+        assert_eq!(combined.points[1].x_numeric(), Some(2.0));
+    }
+
+    #[test]
+    fn test_polar_source_and_degrees() {
+        let dataset = parse_data_source("polar:cos(2*theta); 1", &SourceOptions::new().with_points(Some(20))).unwrap();
+        assert!(dataset.is_polar);
+        assert_eq!(dataset.series.len(), 2);
+
+        let cartesian = || Dataset::new_numeric(
+            vec![DataPoint::Numeric(180.0, 1.0)],
+            "angle".to_string(),
+            "gain".to_string(),
+        );
+        let polar = cartesian().into_polar_from_degrees().unwrap();
+        assert!((polar.points[0].x_numeric().unwrap() - std::f64::consts::PI).abs() < 1e-12);
+
+        assert!(Dataset::combine(vec![cartesian(), dataset], "y").is_err());
+    }
+
+    #[test]
+    fn test_combine_rejects_categorical() {
+        let numeric = Dataset::new_numeric(vec![DataPoint::Numeric(0.0, 1.0)], "x".to_string(), "y".to_string());
+        let categorical = Dataset::new_categorical(
+            vec![DataPoint::Categorical("A".to_string(), 1.0)],
+            "x".to_string(),
+            "y".to_string(),
+            vec!["A".to_string()],
+        );
+        assert!(Dataset::combine(vec![numeric, categorical], "y").is_err());
+    }
+
+    #[test]
+    fn test_unnamed_dataset_uses_y_label() {
+        let dataset = Dataset::new_numeric(vec![DataPoint::Numeric(0.0, 1.0)], "x".to_string(), "Sales".to_string());
+        assert_eq!(dataset.series_slices()[0].0, "Sales");
+    }
+}
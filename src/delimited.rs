@@ -0,0 +1,280 @@
+use anyhow::{Result, anyhow};
+use csv::{ReaderBuilder, Trim};
+use std::io::{BufRead, BufReader, Cursor, Read};
+use crate::input::{self, ReadOptions};
+
+/// Lines examined when detecting the delimiter
+const SAMPLE_LINES: usize = 20;
+
+/// Single-character delimiters tried by auto-detection, in order of preference
+const CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Lines starting with this character are comments
+const COMMENT: u8 = b'#';
+
+/// How fields are separated on a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    /// A single separator character; fields may be quoted
+    Char(u8),
+    /// Runs of spaces and tabs, as in .dat files
+    Whitespace,
+}
+
+impl Delimiter {
+    /// Parse a delimiter given as a character or a name: "tab", "space"/"whitespace",
+    /// "comma", "semicolon" or "pipe"
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "tab" | "\\t" | "\t" => Ok(Delimiter::Char(b'\t')),
+            "space" | "whitespace" | " " => Ok(Delimiter::Whitespace),
+            "comma" => Ok(Delimiter::Char(b',')),
+            "semicolon" => Ok(Delimiter::Char(b';')),
+            "pipe" => Ok(Delimiter::Char(b'|')),
+            _ if text.len() == 1 && text.is_ascii() => Ok(Delimiter::Char(text.as_bytes()[0])),
+            _ => Err(anyhow!(
+                "Delimiter must be a single character, tab, space, comma, semicolon or pipe, got: {}",
+                text
+            )),
+        }
+    }
+
+    /// The delimiter that splits the sample lines most consistently: the same
+    /// number of separators on every line, then the most fields. Falls back to
+    /// whitespace when that gives a steady column count, and to commas otherwise.
+    fn detect(lines: &[&str]) -> Self {
+        let best = CANDIDATES.iter()
+            .filter_map(|&candidate| {
+                let counts: Vec<usize> = lines.iter()
+                    .map(|line| line.bytes().filter(|b| *b == candidate).count())
+                    .collect();
+                let min = *counts.iter().min()?;
+                let consistent = counts.iter().all(|count| *count == min);
+                (min > 0).then_some(((consistent, min), candidate))
+            })
+            // Reversed, since max_by_key keeps the last of equal scores
+            .rev()
+            .max_by_key(|(score, _)| *score);
+        if let Some((_, candidate)) = best {
+            return Delimiter::Char(candidate);
+        }
+
+        let mut widths = lines.iter().map(|line| line.split_whitespace().count());
+        match widths.next() {
+            Some(width) if width > 1 && widths.all(|w| w == width) => Delimiter::Whitespace,
+            _ => Delimiter::Char(b','),
+        }
+    }
+}
+
+/// A row of fields and the line of the input it started on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+impl Record {
+    /// Read a numeric field: `None` for a missing value such as NA or an empty
+    /// cell, and an error naming the source, line and column when the field is
+    /// absent or not a number
+    pub fn number(&self, column: usize, source: &str, headers: &[String]) -> Result<Option<f64>> {
+        let location = || {
+            let name = headers.get(column).map(|h| format!(" ('{}')", h)).unwrap_or_default();
+            format!("{}, line {}, column {}{}", input::display_name(source), self.line, column + 1, name)
+        };
+        let field = self.fields.get(column)
+            .ok_or_else(|| anyhow!("Missing value in {}", location()))?;
+        if input::is_missing(field) {
+            return Ok(None);
+        }
+        field.parse()
+            .map(Some)
+            .map_err(|_| anyhow!("Invalid number '{}' in {}", field, location()))
+    }
+}
+
+/// Column labels and a stream of data rows
+pub struct Table {
+    pub headers: Vec<String>,
+    pub records: Box<dyn Iterator<Item = Result<Record>>>,
+}
+
+/// Read delimited text: skip leading rows, detect the delimiter from the
+/// first lines unless one is given, drop `#` comments and blank lines, and
+/// take labels from the header row or, without one, from column positions.
+/// Rows are read lazily as the records are consumed.
+pub fn read_table(input: Box<dyn Read>, options: &ReadOptions) -> Result<Table> {
+    let mut input = BufReader::new(input);
+    let mut line = String::new();
+    for _ in 0..options.skip_rows {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+    }
+
+    // Buffer the sample lines; they are replayed ahead of the rest of the input
+    let mut sample = Vec::new();
+    let mut data_lines = Vec::new();
+    while data_lines.len() < SAMPLE_LINES {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        sample.extend_from_slice(line.as_bytes());
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with(COMMENT as char) {
+            data_lines.push(trimmed.to_string());
+        }
+    }
+    let delimiter = options.delimiter.unwrap_or_else(|| {
+        Delimiter::detect(&data_lines.iter().map(String::as_str).collect::<Vec<_>>())
+    });
+
+    let replayed: Box<dyn Read> = Box::new(Cursor::new(sample).chain(input));
+    let first_line = options.skip_rows + 1;
+    let mut records: Box<dyn Iterator<Item = Result<Record>>> = match delimiter {
+        Delimiter::Char(byte) => char_records(replayed, byte, first_line),
+        Delimiter::Whitespace => whitespace_records(replayed, first_line),
+    };
+
+    let first = records.next().transpose()?;
+    let headers = match (&first, options.no_header) {
+        (Some(record), false) => record.fields.clone(),
+        (Some(record), true) => (1..=record.fields.len()).map(|i| format!("column {}", i)).collect(),
+        (None, _) => Vec::new(),
+    };
+    if options.no_header {
+        records = Box::new(first.map(Ok).into_iter().chain(records));
+    }
+
+    Ok(Table { headers, records })
+}
+
+/// Records split on a single character, with quoting handled by the csv reader
+fn char_records(input: Box<dyn Read>, delimiter: u8, first_line: usize) -> Box<dyn Iterator<Item = Result<Record>>> {
+    let reader = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .trim(Trim::All)
+        .flexible(true)
+        .from_reader(BlankComments { input: BufReader::new(input), line: Vec::new(), offset: 0 });
+
+    Box::new(reader.into_records().filter_map(move |result| {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e.into())),
+        };
+        if record.iter().all(str::is_empty) {
+            return None;
+        }
+        let line = record.position().map_or(0, |p| p.line() as usize) + first_line - 1;
+        Some(Ok(Record { line, fields: record.iter().map(str::to_string).collect() }))
+    }))
+}
+
+/// Replaces comment and blank lines with a lone space. The csv reader reads
+/// that as an empty record, which is dropped, rather than skipping the line
+/// without counting it, so record line numbers match the file.
+struct BlankComments<R> {
+    input: R,
+    line: Vec<u8>,
+    offset: usize,
+}
+
+impl<R: BufRead> Read for BlankComments<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.offset == self.line.len() {
+            self.line.clear();
+            self.offset = 0;
+            if self.input.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(0);
+            }
+            let content = self.line.trim_ascii();
+            if content.is_empty() || content[0] == COMMENT {
+                self.line.clear();
+                self.line.extend_from_slice(b" \n");
+            }
+        }
+        let count = buf.len().min(self.line.len() - self.offset);
+        buf[..count].copy_from_slice(&self.line[self.offset..self.offset + count]);
+        self.offset += count;
+        Ok(count)
+    }
+}
+
+/// Records split on runs of whitespace, one per non-blank, non-comment line
+fn whitespace_records(input: Box<dyn Read>, first_line: usize) -> Box<dyn Iterator<Item = Result<Record>>> {
+    Box::new(BufReader::new(input)
+        .lines()
+        .enumerate()
+        .filter_map(move |(index, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with(COMMENT as char) {
+                return None;
+            }
+            let fields = trimmed.split_whitespace().map(str::to_string).collect();
+            Some(Ok(Record { line: first_line + index, fields }))
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str, options: &ReadOptions) -> (Vec<String>, Vec<Record>) {
+        let table = read_table(Box::new(Cursor::new(text.to_string().into_bytes())), options).unwrap();
+        let records = table.records.collect::<Result<Vec<_>>>().unwrap();
+        (table.headers, records)
+    }
+
+    fn fields(records: &[Record]) -> Vec<Vec<&str>> {
+        records.iter().map(|r| r.fields.iter().map(String::as_str).collect()).collect()
+    }
+
+    #[test]
+    fn test_detects_delimiters() {
+        assert_eq!(Delimiter::detect(&["a,b", "1,2"]), Delimiter::Char(b','));
+        assert_eq!(Delimiter::detect(&["a\tb\tc", "1\t2\t3"]), Delimiter::Char(b'\t'));
+        assert_eq!(Delimiter::detect(&["x;y", "1,5;2"]), Delimiter::Char(b';'));
+        assert_eq!(Delimiter::detect(&["time|value", "1|2"]), Delimiter::Char(b'|'));
+        assert_eq!(Delimiter::detect(&["x   y", "1.0  2.0"]), Delimiter::Whitespace);
+        assert_eq!(Delimiter::detect(&["value", "1"]), Delimiter::Char(b','));
+        // Ties go to the earlier candidate
+        assert_eq!(Delimiter::detect(&["a,b|c", "1,2|3"]), Delimiter::Char(b','));
+        assert_eq!(Delimiter::detect(&["a;b\tc", "1;2\t3"]), Delimiter::Char(b'\t'));
+    }
+
+    #[test]
+    fn test_comments_skip_rows_and_line_numbers() {
+        let text = "exported by logger v2\n\n# units: s, m\nt;h\n1;10\n# pause\n2;\"20\"\n";
+        let options = ReadOptions::new().with_skip_rows(1);
+        let (headers, records) = table(text, &options);
+        assert_eq!(headers, vec!["t", "h"]);
+        assert_eq!(fields(&records), vec![vec!["1", "10"], vec!["2", "20"]]);
+        assert_eq!(records.iter().map(|r| r.line).collect::<Vec<_>>(), vec![5, 7]);
+    }
+
+    #[test]
+    fn test_whitespace_without_header() {
+        let text = "# x y\n  0.0   1.5\n1.0\t2.5\n\n2.0 3.5\n";
+        let options = ReadOptions::new().with_no_header(true);
+        let (headers, records) = table(text, &options);
+        assert_eq!(headers, vec!["column 1", "column 2"]);
+        assert_eq!(fields(&records), vec![vec!["0.0", "1.5"], vec!["1.0", "2.5"], vec!["2.0", "3.5"]]);
+        assert_eq!(records[2].line, 5);
+    }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(Delimiter::parse("tab").unwrap(), Delimiter::Char(b'\t'));
+        assert_eq!(Delimiter::parse(";").unwrap(), Delimiter::Char(b';'));
+        assert_eq!(Delimiter::parse("space").unwrap(), Delimiter::Whitespace);
+        assert!(Delimiter::parse("::").is_err());
+    }
+}
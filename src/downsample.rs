@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use crate::data::DataPoint;
+
+/// The points a bucket keeps: the leftmost and rightmost, the lowest and the
+/// highest, and where the last gap fell
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    first: (f64, f64),
+    last: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+    gap: Option<f64>,
+}
+
+impl Bucket {
+    fn new(x: f64, y: f64) -> Self {
+        Self { first: (x, y), last: (x, y), min: (x, y), max: (x, y), gap: None }
+    }
+
+    fn gap(x: f64) -> Self {
+        Self { first: (x, f64::NAN), last: (x, f64::NAN), min: (x, f64::INFINITY), max: (x, f64::NEG_INFINITY), gap: Some(x) }
+    }
+
+    fn has_value(&self) -> bool {
+        self.min.1.is_finite()
+    }
+
+    /// Fold in a later bucket, or a point, covering the same x range
+    fn merge(&mut self, other: &Bucket) {
+        if !other.has_value() {
+            self.gap = other.gap.or(self.gap);
+            return;
+        }
+        if !self.has_value() {
+            let gap = self.gap;
+            *self = *other;
+            self.gap = other.gap.or(gap);
+            return;
+        }
+        if other.first.0 < self.first.0 {
+            self.first = other.first;
+        }
+        if other.last.0 >= self.last.0 {
+            self.last = other.last;
+        }
+        if other.min.1 < self.min.1 {
+            self.min = other.min;
+        }
+        if other.max.1 > self.max.1 {
+            self.max = other.max;
+        }
+        self.gap = other.gap.or(self.gap);
+    }
+}
+
+/// Streaming M4 downsampling. x is divided into equal buckets, and each keeps
+/// only its first, last, lowest and highest point: all that a line drawn at
+/// that resolution can show. The bucket width starts from the spacing of the
+/// first points and doubles, merging neighbouring buckets, whenever the data
+/// spans more than the target count, so memory depends on the plot width
+/// rather than on how many rows arrive. Rows may come in any x order.
+#[derive(Debug)]
+pub struct M4 {
+    target: usize,
+    origin: f64,
+    /// Bucket width; zero until two distinct x values have been seen
+    width: f64,
+    buckets: BTreeMap<i64, Bucket>,
+}
+
+impl M4 {
+    /// An aggregator keeping at most `target` buckets
+    pub fn new(target: usize) -> Self {
+        Self { target: target.max(1), origin: f64::NAN, width: 0.0, buckets: BTreeMap::new() }
+    }
+
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.insert(x, Bucket::new(x, y));
+    }
+
+    /// Record a gap at `x`, so lines stay broken there
+    pub fn push_gap(&mut self, x: f64) {
+        self.insert(x, Bucket::gap(x));
+    }
+
+    fn insert(&mut self, x: f64, bucket: Bucket) {
+        if !x.is_finite() {
+            return;
+        }
+        if self.origin.is_nan() {
+            self.origin = x;
+        }
+        if self.width == 0.0 && x != self.origin {
+            // Fine enough that the first doubling sets the real resolution
+            self.width = (x - self.origin).abs() / self.target as f64;
+        }
+        let index = self.index(x);
+        self.buckets.entry(index)
+            .and_modify(|existing| existing.merge(&bucket))
+            .or_insert(bucket);
+
+        while self.span() > self.target {
+            self.coarsen();
+        }
+    }
+
+    fn index(&self, x: f64) -> i64 {
+        if self.width == 0.0 {
+            0
+        } else {
+            ((x - self.origin) / self.width).floor() as i64
+        }
+    }
+
+    /// Buckets from the leftmost occupied one to the rightmost
+    fn span(&self) -> usize {
+        match (self.buckets.first_key_value(), self.buckets.last_key_value()) {
+            (Some((first, _)), Some((last, _))) => (last - first) as usize + 1,
+            _ => 0,
+        }
+    }
+
+    /// Double the bucket width. The grid stays anchored at the origin, so
+    /// each new bucket is exactly two old ones.
+    fn coarsen(&mut self) {
+        self.width *= 2.0;
+        let mut merged: BTreeMap<i64, Bucket> = BTreeMap::new();
+        for (index, bucket) in std::mem::take(&mut self.buckets) {
+            merged.entry(index.div_euclid(2))
+                .and_modify(|existing| existing.merge(&bucket))
+                .or_insert(bucket);
+        }
+        self.buckets = merged;
+    }
+
+    /// The kept points in x order, with gap markers where gaps fell
+    pub fn into_points(self) -> Vec<DataPoint> {
+        let mut points = Vec::with_capacity(self.buckets.len() * 4);
+        for bucket in self.buckets.values() {
+            let mut kept = Vec::with_capacity(5);
+            if bucket.has_value() {
+                kept.extend([bucket.first, bucket.min, bucket.max, bucket.last]);
+            }
+            if let Some(x) = bucket.gap {
+                kept.push((x, f64::NAN));
+            }
+            kept.sort_by(|a, b| a.0.total_cmp(&b.0));
+            kept.dedup_by(|a, b| a.0 == b.0 && (a.1 == b.1 || (a.1.is_nan() && b.1.is_nan())));
+            points.extend(kept.into_iter().map(|(x, y)| DataPoint::Numeric(x, y)));
+        }
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(points: &[DataPoint]) -> Vec<(f64, f64)> {
+        points.iter().map(|p| (p.x_numeric().unwrap(), p.y())).collect()
+    }
+
+    #[test]
+    fn test_keeps_extremes_within_bounded_buckets() {
+        let mut m4 = M4::new(100);
+        for i in 0..200_000 {
+            let x = i as f64 * 0.005;
+            let y = if i == 154_321 { 50.0 } else if i == 23_456 { -50.0 } else { x.sin() };
+            m4.push(x, y);
+            assert!(m4.buckets.len() <= 100);
+        }
+        let points = values(&m4.into_points());
+        assert!(points.len() <= 400 && points.len() > 200, "{}", points.len());
+        assert_eq!(points[0], (0.0, 0.0));
+        assert_eq!(points.last().unwrap().0, 999.995);
+        assert!(points.contains(&(771.605, 50.0)) && points.contains(&(117.28, -50.0)));
+        assert!(points.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+
+    #[test]
+    fn test_unordered_input() {
+        let mut m4 = M4::new(50);
+        for i in 0..5_000 {
+            let x = ((i * 7919) % 5_000) as f64;
+            m4.push(x, if x == 2_500.0 { 9.0 } else { (x * 0.1).cos() });
+        }
+        assert!(m4.buckets.len() <= 50);
+        let points = values(&m4.into_points());
+        assert_eq!(points[0].0, 0.0);
+        assert_eq!(points.last().unwrap().0, 4_999.0);
+        assert!(points.contains(&(2_500.0, 9.0)));
+    }
+
+    #[test]
+    fn test_gaps_survive_merging() {
+        let mut m4 = M4::new(4);
+        for x in 0..100 {
+            if x == 40 {
+                m4.push_gap(40.0);
+            } else {
+                m4.push(x as f64, 1.0);
+            }
+        }
+        let points = m4.into_points();
+        assert!(points.iter().any(|p| p.is_gap() && p.x_numeric() == Some(40.0)));
+        assert!(points.len() <= 4 * 5);
+    }
+}
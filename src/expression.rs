@@ -0,0 +1,327 @@
+use anyhow::{Result, anyhow};
+
+/// Two-character operators, matched before their one-character prefixes
+const DOUBLE_OPERATORS: [&str; 6] = ["==", "!=", "<=", ">=", "&&", "||"];
+
+/// Binary operators that may stand between two values
+const SINGLE_OPERATORS: &str = "+-*/%^<>";
+
+/// Operators that may prefix a value
+const PREFIX_OPERATORS: [&str; 2] = ["-", "!"];
+
+/// Names every expression can use besides its functions and variables
+const CONSTANTS: [&str; 4] = ["pi", "e", "true", "false"];
+
+/// Built-in control flow evaluated by the expression engine itself
+const BUILTIN_FUNCTIONS: [&str; 1] = ["if"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Number,
+    Identifier,
+    Operator,
+    Open,
+    Close,
+    Comma,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    /// Character offset of the token in the expression
+    column: usize,
+}
+
+/// Check an expression before it is evaluated: its syntax, and that every
+/// function it calls and every variable it reads is known. Errors point at the
+/// offending spot with a caret and suggest the closest known name.
+pub fn validate(expression: &str, functions: &[&str], variables: &[&str]) -> Result<()> {
+    let tokens = tokenize(expression)?;
+    check_syntax(expression, &tokens)?;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Identifier || token.text.contains("::") {
+            continue;
+        }
+        let called = tokens.get(i + 1).is_some_and(|next| next.kind == TokenKind::Open);
+        let (known, what): (Vec<&str>, &str) = if called {
+            (functions.iter().chain(&BUILTIN_FUNCTIONS).copied().collect(), "function")
+        } else {
+            (variables.iter().chain(&CONSTANTS).copied().collect(), "variable")
+        };
+        if known.contains(&token.text) {
+            continue;
+        }
+
+        let mut message = format!("Unknown {} '{}'\n{}", what, token.text, pointer(expression, token));
+        if let Some(suggestion) = closest(token.text, &known) {
+            message.push_str(&format!("\nDid you mean '{}'?", suggestion));
+        } else if !called && functions.contains(&token.text) {
+            message.push_str(&format!("\n'{}' is a function; call it as {}(...)", token.text, token.text));
+        }
+        return Err(anyhow!(message));
+    }
+    Ok(())
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().enumerate().peekable();
+
+    while let Some((column, (start, c))) = chars.next() {
+        let kind = if c.is_whitespace() {
+            continue;
+        } else if c.is_ascii_digit() || (c == '.' && next_is_digit(expression, start + 1)) {
+            TokenKind::Number
+        } else if c.is_alphabetic() || c == '_' {
+            TokenKind::Identifier
+        } else {
+            match c {
+                '(' => TokenKind::Open,
+                ')' => TokenKind::Close,
+                ',' => TokenKind::Comma,
+                c if SINGLE_OPERATORS.contains(c) || "=!&|".contains(c) => TokenKind::Operator,
+                _ => {
+                    let token = Token { kind: TokenKind::Operator, text: &expression[start..start + c.len_utf8()], column };
+                    return Err(syntax_error(expression, &token, &format!("unexpected character '{}'", c)));
+                }
+            }
+        };
+
+        let mut end = start + c.len_utf8();
+        match kind {
+            TokenKind::Number => {
+                while let Some((_, (i, c))) = chars.peek().copied() {
+                    // An exponent is part of the number only when digits follow it
+                    let exponent = (c == 'e' || c == 'E') && {
+                        let rest = &expression[i + 1..];
+                        let rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+                        next_is_digit(rest, 0)
+                    };
+                    if c.is_ascii_digit() || c == '.' {
+                        chars.next();
+                    } else if exponent {
+                        chars.next();
+                        if let Some((_, (_, '+' | '-'))) = chars.peek() {
+                            chars.next();
+                        }
+                    } else {
+                        break;
+                    }
+                    end = chars.peek().map_or(expression.len(), |(_, (i, _))| *i);
+                }
+            }
+            TokenKind::Identifier => {
+                while let Some((_, (i, c))) = chars.peek().copied() {
+                    // Namespaced built-ins such as math::ln
+                    let namespace = c == ':' && expression[i..].starts_with("::");
+                    if c.is_alphanumeric() || c == '_' {
+                        chars.next();
+                    } else if namespace {
+                        chars.next();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                    end = chars.peek().map_or(expression.len(), |(_, (i, _))| *i);
+                }
+            }
+            TokenKind::Operator if DOUBLE_OPERATORS.iter().any(|op| expression[start..].starts_with(op)) => {
+                chars.next();
+                end = start + 2;
+            }
+            _ => {}
+        }
+
+        tokens.push(Token { kind, text: &expression[start..end], column });
+    }
+    Ok(tokens)
+}
+
+fn next_is_digit(text: &str, index: usize) -> bool {
+    text[index..].chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Walk the tokens alternating between expecting a value and expecting an
+/// operator, tracking open parentheses, and report the first token that
+/// cannot appear where it does
+fn check_syntax(expression: &str, tokens: &[Token]) -> Result<()> {
+    // Open parentheses, and whether each began a function call
+    let mut open: Vec<(&Token, bool)> = Vec::new();
+    let mut expect_value = true;
+    let mut previous: Option<&Token> = None;
+
+    for token in tokens {
+        let ok = match (expect_value, token.kind) {
+            (true, TokenKind::Number | TokenKind::Identifier) => {
+                expect_value = false;
+                true
+            }
+            (true, TokenKind::Open) => {
+                open.push((token, false));
+                true
+            }
+            (true, TokenKind::Operator) => PREFIX_OPERATORS.contains(&token.text),
+            // An empty argument list, as in f()
+            (true, TokenKind::Close) => {
+                let empty_call = previous.is_some_and(|p| p.kind == TokenKind::Open)
+                    && open.last().is_some_and(|(_, call)| *call);
+                if empty_call {
+                    open.pop();
+                    expect_value = false;
+                }
+                empty_call
+            }
+            (false, TokenKind::Operator) => {
+                let binary = token.text.len() == 2 || SINGLE_OPERATORS.contains(token.text);
+                expect_value = binary;
+                binary
+            }
+            (false, TokenKind::Open) => {
+                // Only a function name may be followed directly by a parenthesis
+                let call = previous.is_some_and(|p| p.kind == TokenKind::Identifier);
+                if call {
+                    open.push((token, true));
+                    expect_value = true;
+                }
+                call
+            }
+            (false, TokenKind::Close) => {
+                if open.pop().is_none() {
+                    return Err(syntax_error(expression, token, "unmatched ')'"));
+                }
+                true
+            }
+            (false, TokenKind::Comma) => {
+                expect_value = true;
+                !open.is_empty()
+            }
+            (true, TokenKind::Comma) => false,
+            (false, TokenKind::Number | TokenKind::Identifier) => {
+                let hint = if previous.is_some_and(|p| p.kind == TokenKind::Number) {
+                    format!(" (write {}*{} to multiply)", previous.unwrap().text, token.text)
+                } else {
+                    String::new()
+                };
+                return Err(syntax_error(expression, token, &format!("expected an operator before '{}'{}", token.text, hint)));
+            }
+        };
+
+        if !ok {
+            let message = if expect_value {
+                format!("expected a value, found '{}'", token.text)
+            } else {
+                format!("unexpected '{}'", token.text)
+            };
+            return Err(syntax_error(expression, token, &message));
+        }
+        previous = Some(token);
+    }
+
+    if let Some((unclosed, _)) = open.last() {
+        return Err(syntax_error(expression, unclosed, "unclosed '('"));
+    }
+    match previous {
+        None => Err(anyhow!("Syntax error: empty expression")),
+        Some(last) if expect_value => {
+            Err(syntax_error(expression, last, &format!("expression ends after '{}'", last.text)))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn syntax_error(expression: &str, token: &Token, message: &str) -> anyhow::Error {
+    anyhow!("Syntax error: {}\n{}", message, pointer(expression, token))
+}
+
+/// The expression with carets under a token
+fn pointer(expression: &str, token: &Token) -> String {
+    format!(
+        "  {}\n  {}{}",
+        expression,
+        " ".repeat(token.column),
+        "^".repeat(token.text.chars().count().max(1)),
+    )
+}
+
+/// Closest known name within a couple of edits, scaled to the name's length;
+/// a one-letter name is too short to guess at
+fn closest<'a>(name: &str, known: &[&'a str]) -> Option<&'a str> {
+    let length = name.chars().count();
+    let limit = (length / 3).clamp(1, 2).min(length - 1);
+    known.iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance, counting an adjacent swap as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            row[j] = substitution.min(rows[i - 1][j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUNCTIONS: [&str; 4] = ["sin", "cos", "sqrt", "max"];
+
+    fn error(expression: &str, variables: &[&str]) -> String {
+        validate(expression, &FUNCTIONS, variables).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_valid_expressions() {
+        for expression in [
+            "sin(x)^2 + cos(x)^2",
+            "-x * -2.5e-3",
+            "if(x >= 0 && x != 1, sqrt(x), -x)",
+            "max(1, x, 3) % 2",
+            "!(x > 1) || true",
+            "math::ln(x) + pi * e",
+            "1.5E+2 * .5",
+        ] {
+            assert!(validate(expression, &FUNCTIONS, &["x"]).is_ok(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_syntax_errors_point_at_the_problem() {
+        assert_eq!(error("sin(x))", &["x"]), "Syntax error: unmatched ')'\n  sin(x))\n        ^");
+        assert_eq!(error("(x + 1", &["x"]), "Syntax error: unclosed '('\n  (x + 1\n  ^");
+        assert_eq!(error("x * ", &["x"]), "Syntax error: expression ends after '*'\n  x * \n    ^");
+        assert!(error("x + * 2", &["x"]).starts_with("Syntax error: expected a value, found '*'\n  x + * 2\n      ^"));
+        assert!(error("2x", &["x"]).contains("write 2*x to multiply"));
+        assert!(error("x = 1", &["x"]).contains("unexpected '='"));
+        assert!(error("x $ 1", &["x"]).contains("unexpected character '$'"));
+        assert!(error("", &["x"]).contains("empty expression"));
+    }
+
+    #[test]
+    fn test_unknown_names_get_suggestions() {
+        assert_eq!(
+            error("cos(3*tehta)", &["theta"]),
+            "Unknown variable 'tehta'\n  cos(3*tehta)\n        ^^^^^\nDid you mean 'theta'?"
+        );
+        assert!(error("sinn(x)", &["x"]).ends_with("Did you mean 'sin'?"));
+        assert!(error("sqr(x)", &["x"]).ends_with("Did you mean 'sqrt'?"));
+        assert!(error("x * sin", &["x"]).ends_with("'sin' is a function; call it as sin(...)"));
+        assert!(!error("x + velocity", &["x"]).contains("Did you mean"));
+        assert!(!error("x + r", &["x"]).contains("Did you mean"));
+    }
+}
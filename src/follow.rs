@@ -0,0 +1,381 @@
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::data::{self, DataPoint, Dataset};
+use crate::input::{self, BadRowPolicy, Compression, InputFormat, ReadOptions, Window, STDIN_SOURCE};
+use crate::{json, pattern};
+
+/// Shortest time between redraws, and how often an idle window is checked for expired rows
+const REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long to wait at the end of a file before looking for more
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Latest lines kept when no window is given, and the most a duration window
+/// keeps, so a long-running source does not grow the buffer, and the text
+/// re-read on every redraw, without bound
+pub const DEFAULT_WINDOW_ROWS: usize = 10_000;
+
+/// Watch a file or stdin, redrawing the plot in place on `output` as lines
+/// arrive. `render` turns the rows inside the window, by default the latest
+/// 10,000, into a full frame; the previous frame is erased with ANSI cursor movement
+/// before the next is written. A file is followed until interrupted, stdin
+/// until it closes.
+pub fn follow(
+    source: &str,
+    options: &ReadOptions,
+    output: &mut impl Write,
+    render: impl Fn(Dataset) -> Result<String>,
+) -> Result<()> {
+    if !data::is_file_source(source) {
+        return Err(anyhow!("--follow watches a file or stdin, not {}", source));
+    }
+    let regex = options.regex.as_deref().map(pattern::compile).transpose()?;
+    if regex.is_none() && !matches!(options.format_for(source), InputFormat::Delimited | InputFormat::Ndjson) {
+        return Err(anyhow!(
+            "--follow reads line by line, so {} must be delimited text, NDJSON or read with --regex",
+            input::display_name(source)
+        ));
+    }
+    if source != STDIN_SOURCE && Compression::of_file(source)? != Compression::None {
+        return Err(anyhow!("--follow cannot watch compressed file {}; pipe it through a decompressor to stdin", source));
+    }
+
+    let lines = spawn_reader(source);
+    let mut buffer = Buffer::new(source, options);
+    let mut shown = 0;
+    let mut changed = false;
+    let mut next_draw = Instant::now();
+    loop {
+        let ended = match lines.recv_timeout(REFRESH_INTERVAL) {
+            Ok(line) => {
+                buffer.push(line?, Instant::now());
+                changed = true;
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        // Take whatever else has arrived, so a burst of rows is drawn once
+        for line in lines.try_iter() {
+            buffer.push(line?, Instant::now());
+            changed = true;
+        }
+        changed |= buffer.expire(Instant::now());
+
+        if changed && buffer.has_rows() && (ended || Instant::now() >= next_draw) {
+            let started = Instant::now();
+            let (dataset, bad_row) = buffer.dataset_skipping_bad_rows(source, options, regex.as_ref())?;
+            let mut frame = render(dataset)?;
+            if let Some(error) = bad_row {
+                if !frame.ends_with('\n') {
+                    frame.push('\n');
+                }
+                frame.push_str(&format!("Warning: {}; bad rows are skipped\n", error));
+            }
+            shown = redraw(output, &frame, shown)?;
+            changed = false;
+            // Large windows take a while to redraw; back off rather than fall behind the input
+            next_draw = Instant::now() + REFRESH_INTERVAL.max(started.elapsed() * 4);
+        }
+        if ended {
+            return Ok(());
+        }
+    }
+}
+
+/// Write `frame` over the previous one, which took up `shown` lines, and
+/// return how many lines the new one takes. Line wrapping is turned off while
+/// the frame is written, so a line wider than the terminal is cut off at the
+/// edge rather than spilling onto rows the next redraw would not move back over.
+fn redraw(output: &mut impl Write, frame: &str, shown: usize) -> io::Result<usize> {
+    if shown > 0 {
+        // To the start of the previous frame's first line
+        write!(output, "\x1b[{}F", shown)?;
+    }
+    // Clear to the end of the screen, in case the new frame is shorter
+    write!(output, "\x1b[?7l\x1b[J{}", frame)?;
+    if !frame.ends_with('\n') {
+        writeln!(output)?;
+    }
+    write!(output, "\x1b[?7h")?;
+    output.flush()?;
+    Ok(frame.lines().count())
+}
+
+/// Read the source on a thread of its own, sending each line as it completes
+fn spawn_reader(source: &str) -> Receiver<Result<String>> {
+    let (sender, receiver) = mpsc::channel();
+    let source = source.to_string();
+    thread::spawn(move || {
+        if let Err(error) = send_lines(&source, &sender) {
+            let _ = sender.send(Err(error));
+        }
+    });
+    receiver
+}
+
+fn send_lines(source: &str, sender: &Sender<Result<String>>) -> Result<()> {
+    let input: Box<dyn Read> = if source == STDIN_SOURCE {
+        input::open_input(source)?
+    } else {
+        Box::new(Tail { file: File::open(source)? })
+    };
+    let mut input = BufReader::new(input);
+    let mut bytes = Vec::new();
+    loop {
+        bytes.clear();
+        if input.read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&bytes).trim_end_matches(['\n', '\r']).to_string();
+        // The receiver is gone once plotting has stopped
+        if sender.send(Ok(line)).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// A file read as it grows: at its end, reading waits for more rather than
+/// finishing, and a file truncated in place, as by log rotation, is read
+/// again from the top
+struct Tail {
+    file: File,
+}
+
+impl Read for Tail {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let count = self.file.read(buf)?;
+            if count > 0 || buf.is_empty() {
+                return Ok(count);
+            }
+            if self.file.metadata()?.len() < self.file.stream_position()? {
+                self.file.seek(SeekFrom::Start(0))?;
+                continue;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// The lines a followed source has produced: those ahead of the data, such
+/// as skipped rows and the header, which every redraw needs, then the data
+/// lines inside the window with the time each arrived
+struct Buffer {
+    window: Window,
+    skip_rows: usize,
+    /// A header line is still to come
+    header: bool,
+    leading: Vec<String>,
+    rows: VecDeque<(Instant, String)>,
+    /// Data lines that have left the window
+    dropped: usize,
+}
+
+impl Buffer {
+    fn new(source: &str, options: &ReadOptions) -> Self {
+        let delimited = options.regex.is_none() && options.format_for(source) == InputFormat::Delimited;
+        Self {
+            window: options.window.unwrap_or(Window::Rows(DEFAULT_WINDOW_ROWS)),
+            skip_rows: options.skip_rows,
+            header: delimited && !options.no_header,
+            leading: Vec::new(),
+            rows: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    fn push(&mut self, line: String, now: Instant) {
+        if self.leading.len() < self.skip_rows {
+            self.leading.push(line);
+            return;
+        }
+        if self.header {
+            // Comments and blank lines may come before the header
+            let content = line.trim();
+            self.header = content.is_empty() || content.starts_with('#');
+            self.leading.push(line);
+            return;
+        }
+
+        self.rows.push_back((now, line));
+        let limit = match self.window {
+            Window::Rows(rows) => rows,
+            Window::Duration(_) => DEFAULT_WINDOW_ROWS,
+        };
+        while self.rows.len() > limit {
+            self.rows.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    /// Drop lines older than a duration window; true when any were dropped
+    fn expire(&mut self, now: Instant) -> bool {
+        let Window::Duration(period) = self.window else {
+            return false;
+        };
+        let before = self.dropped;
+        while self.rows.front().is_some_and(|(arrived, _)| now.duration_since(*arrived) > period) {
+            self.rows.pop_front();
+            self.dropped += 1;
+        }
+        self.dropped > before
+    }
+
+    fn has_rows(&self) -> bool {
+        !self.rows.is_empty()
+    }
+
+    /// Read the window without letting a bad row end the session. The rows
+    /// are re-read on every redraw, so a warning printed while reading would
+    /// repeat and scroll the chart away; unless bad rows are skipped quietly,
+    /// the first is returned instead, for showing below the chart.
+    fn dataset_skipping_bad_rows(
+        &self,
+        source: &str,
+        options: &ReadOptions,
+        regex: Option<&Regex>,
+    ) -> Result<(Dataset, Option<anyhow::Error>)> {
+        let skipping = options.clone().with_on_bad_row(BadRowPolicy::Skip);
+        if options.on_bad_row == BadRowPolicy::Skip {
+            return Ok((self.dataset(source, &skipping, regex)?, None));
+        }
+        match self.dataset(source, &options.clone().with_on_bad_row(BadRowPolicy::Error), regex) {
+            Ok(dataset) => Ok((dataset, None)),
+            // Errors other than a bad row, such as a missing column, fail again here
+            Err(error) => Ok((self.dataset(source, &skipping, regex)?, Some(error))),
+        }
+    }
+
+    /// Read the buffered lines as the source's format, numbering rows from
+    /// where they sit in the whole input
+    fn dataset(&self, source: &str, options: &ReadOptions, regex: Option<&Regex>) -> Result<Dataset> {
+        let mut text = String::new();
+        for line in self.leading.iter().chain(self.rows.iter().map(|(_, line)| line)) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        let input = Cursor::new(text);
+        let options = options.clone().with_row_offset(self.dropped);
+
+        let mut dataset = match regex {
+            Some(regex) => pattern::extract(input, regex, source, &options)?.0,
+            None if options.format_for(source) == InputFormat::Ndjson => json::read_json(Box::new(input), source, &options)?,
+            None => data::read_csv(Box::new(input), source, &options)?,
+        };
+
+        // Rows already in the file when following began all arrived at once;
+        // their timestamps tell which belong in the window
+        if let (Window::Duration(period), true) = (self.window, dataset.is_time) {
+            if let Some(latest) = dataset.points.iter().filter_map(DataPoint::x_numeric).reduce(f64::max) {
+                let start = latest - period.as_secs_f64();
+                dataset.points.retain(|point| point.x_numeric().is_none_or(|x| x >= start));
+            }
+        }
+        Ok(dataset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_window_keeps_header_and_numbering() {
+        let options = ReadOptions::new().with_window(Some(Window::Rows(2)));
+        let mut buffer = Buffer::new("-", &options);
+        let now = Instant::now();
+        for line in ["# loss per step", "step,loss", "1,0.9", "2,0.7", "3,0.6", "4,0.4"] {
+            buffer.push(line.to_string(), now);
+        }
+        let dataset = buffer.dataset("-", &options, None).unwrap();
+        assert_eq!(dataset.y_label, "loss");
+        let points: Vec<(Option<f64>, f64)> = dataset.points.iter().map(|p| (p.x_numeric(), p.y())).collect();
+        assert_eq!(points, vec![(Some(3.0), 0.6), (Some(4.0), 0.4)]);
+
+        // Positions used as x carry on from the lines that left the window
+        let options = options.with_regex(Some(r"loss=(?P<y>\S+)".into()));
+        let regex = pattern::compile(options.regex.as_deref().unwrap()).unwrap();
+        let mut buffer = Buffer::new("train.log", &options);
+        for line in ["loss=0.9", "saving", "loss=0.5", "loss=0.3"] {
+            buffer.push(line.to_string(), now);
+        }
+        let dataset = buffer.dataset("train.log", &options, Some(&regex)).unwrap();
+        let xs: Vec<f64> = dataset.points.iter().filter_map(DataPoint::x_numeric).collect();
+        assert_eq!(xs, vec![3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_rows_are_capped() {
+        let options = ReadOptions::new();
+        let mut buffer = Buffer::new("-", &options);
+        let now = Instant::now();
+        buffer.push("x,y".to_string(), now);
+        for i in 0..DEFAULT_WINDOW_ROWS + 5 {
+            buffer.push(format!("{},{}", i, i), now);
+        }
+        assert_eq!(buffer.rows.len(), DEFAULT_WINDOW_ROWS);
+        assert_eq!(buffer.dropped, 5);
+        let dataset = buffer.dataset("-", &options, None).unwrap();
+        assert_eq!(dataset.points[0].x_numeric(), Some(5.0));
+
+        // A period caps the rows as well, however fast they arrive
+        let options = options.with_window(Some(Window::Duration(Duration::from_secs(7200))));
+        let mut buffer = Buffer::new("-", &options);
+        buffer.push("x,y".to_string(), now);
+        for i in 0..DEFAULT_WINDOW_ROWS + 5 {
+            buffer.push(format!("{},{}", i, i), now);
+        }
+        assert_eq!(buffer.rows.len(), DEFAULT_WINDOW_ROWS);
+    }
+
+    #[test]
+    fn test_bad_rows_are_skipped_with_a_warning() {
+        let options = ReadOptions::new();
+        let mut buffer = Buffer::new("-", &options);
+        let now = Instant::now();
+        for line in ["x,y", "1,2", "2,oops", "3,4"] {
+            buffer.push(line.to_string(), now);
+        }
+        let (dataset, bad_row) = buffer.dataset_skipping_bad_rows("-", &options, None).unwrap();
+        assert_eq!(dataset.points.iter().filter(|p| !p.is_gap()).count(), 2);
+        let message = bad_row.unwrap().to_string();
+        assert!(message.starts_with("Invalid number 'oops' in <stdin>, line 3"), "{}", message);
+
+        let options = options.with_on_bad_row(BadRowPolicy::Skip);
+        let (dataset, bad_row) = buffer.dataset_skipping_bad_rows("-", &options, None).unwrap();
+        assert_eq!(dataset.points.iter().filter(|p| !p.is_gap()).count(), 2);
+        assert!(bad_row.is_none());
+
+        // A column that is not there is not a bad row
+        let options = ReadOptions::new().with_y(Some("z".to_string()));
+        assert!(buffer.dataset_skipping_bad_rows("-", &options, None).is_err());
+    }
+
+    #[test]
+    fn test_duration_window_and_redraw() {
+        let options = ReadOptions::new().with_window(Some(Window::Duration(Duration::from_secs(60))));
+        let mut buffer = Buffer::new("-", &options);
+        let start = Instant::now();
+        buffer.push("x,y".to_string(), start);
+        buffer.push("1,1".to_string(), start);
+        buffer.push("2,4".to_string(), start + Duration::from_secs(45));
+        assert!(!buffer.expire(start + Duration::from_secs(50)));
+        assert!(buffer.expire(start + Duration::from_secs(90)));
+        assert_eq!(buffer.dataset("-", &options, None).unwrap().points.len(), 1);
+
+        let mut output = Vec::new();
+        let shown = redraw(&mut output, "a\nb\n", 0).unwrap();
+        redraw(&mut output, "c", shown).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1b[?7l\x1b[Ja\nb\n\x1b[?7h\x1b[2F\x1b[?7l\x1b[Jc\n\x1b[?7h"
+        );
+    }
+}
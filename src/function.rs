@@ -1,114 +1,647 @@
 use anyhow::{Result, anyhow};
 use evalexpr::*;
-use crate::data::{DataPoint, Dataset, LegacyDataPoint};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use crate::data::{DataPoint, Dataset};
+use crate::{expression, math_functions};
+
+/// Multiple of the median step between samples treated as a suspicious jump
+const JUMP_FACTOR: f64 = 10.0;
+
+/// Halvings used to tell a steep but continuous stretch from a pole or jump
+const DISCONTINUITY_BISECTIONS: usize = 24;
+
+/// Largest distance, in character rows, that adaptive sampling lets the curve
+/// stray from the straight segment between two samples
+const MAX_ERROR_ROWS: f64 = 0.5;
+
+/// Adaptive sampling never splits an interval narrower than this many columns
+const MIN_INTERVAL_COLUMNS: f64 = 1.0 / 8.0;
+
+/// Resolution of the plot that adaptive sampling refines towards, in character cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl AdaptiveSampling {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns: columns.max(1),
+            rows: rows.max(1),
+        }
+    }
+}
+
+/// A sample position and its value, `None` where the function is undefined
+type Sample = (f64, Option<f64>);
+
+/// An interval whose midpoint has been evaluated, ordered by how far the
+/// midpoint strays from the straight segment between the ends
+struct Refinement {
+    error: f64,
+    left: Sample,
+    mid: Sample,
+    right: Sample,
+}
+
+impl PartialEq for Refinement {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Refinement {}
+
+impl PartialOrd for Refinement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Refinement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.error.total_cmp(&other.error)
+    }
+}
+
+/// Names bound by the samplers, which user parameters may not shadow
+const RESERVED_NAMES: &[&str] = &["x", "y", "t", "theta"];
+
+/// Number of values in a sweep written without an explicit count (`a=1..5`)
+const DEFAULT_SWEEP_COUNT: usize = 5;
+
+/// A user-defined constant for expressions (`a=2`), or a sweep over evenly
+/// spaced values (`a=1..5:5`) that plots one curve per value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+impl Parameter {
+    /// Parse `name=value` or `name=start..end[:count]`
+    pub fn parse(text: &str) -> Result<Self> {
+        let (name, value) = text.split_once('=')
+            .ok_or_else(|| anyhow!("Invalid parameter '{}'. Expected name=value or name=start..end:count", text))?;
+        let name = name.trim();
+
+        let mut chars = name.chars();
+        let valid_start = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+        if !valid_start || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(anyhow!("Invalid parameter name '{}'", name));
+        }
+        if RESERVED_NAMES.contains(&name) {
+            return Err(anyhow!("Parameter name '{}' is reserved for the plotted variable", name));
+        }
+
+        let number = |s: &str| s.trim().parse::<f64>()
+            .map_err(|_| anyhow!("Invalid number '{}' in parameter '{}'", s.trim(), text));
+
+        let values = match value.split_once("..") {
+            None => vec![number(value)?],
+            Some((start, rest)) => {
+                let (end, count) = match rest.split_once(':') {
+                    Some((end, count)) => {
+                        let count = count.trim().parse::<usize>()
+                            .map_err(|_| anyhow!("Invalid sweep count '{}' in parameter '{}'", count.trim(), text))?;
+                        (end, count)
+                    }
+                    None => (rest, DEFAULT_SWEEP_COUNT),
+                };
+                let (start, end) = (number(start)?, number(end)?);
+                match count {
+                    0 => return Err(anyhow!("Sweep count must be greater than 0 in parameter '{}'", text)),
+                    1 => vec![start],
+                    _ => (0..count)
+                        .map(|i| start + (end - start) * i as f64 / (count - 1) as f64)
+                        .collect(),
+                }
+            }
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            values,
+        })
+    }
+
+    pub fn is_sweep(&self) -> bool {
+        self.values.len() > 1
+    }
+}
+
+/// One binding of every parameter, with a legend suffix naming the swept ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterSet {
+    pub bindings: Vec<(String, f64)>,
+    pub label: Option<String>,
+}
+
+impl ParameterSet {
+    /// Series label for a curve drawn with these bindings
+    pub fn series_label(&self, expression: &str) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", expression, label),
+            None => expression.to_string(),
+        }
+    }
+}
+
+/// Every combination of parameter values, one set per curve to draw. Without
+/// sweeps this is a single set binding the fixed values.
+pub fn parameter_sets(parameters: &[Parameter]) -> Vec<ParameterSet> {
+    let mut sets = vec![Vec::new()];
+    for parameter in parameters {
+        sets = sets.into_iter()
+            .flat_map(|bindings: Vec<(String, f64)>| {
+                parameter.values.iter().map(move |value| {
+                    let mut bindings = bindings.clone();
+                    bindings.push((parameter.name.clone(), *value));
+                    bindings
+                })
+            })
+            .collect();
+    }
+
+    sets.into_iter()
+        .map(|bindings| {
+            let swept: Vec<String> = parameters.iter()
+                .zip(&bindings)
+                .filter(|(parameter, _)| parameter.is_sweep())
+                .map(|(_, (name, value))| format!("{}={}", name, format_parameter_value(*value)))
+                .collect();
+            let label = (!swept.is_empty()).then(|| swept.join(", "));
+            ParameterSet { bindings, label }
+        })
+        .collect()
+}
+
+/// Format a parameter value for a legend, hiding floating point noise from sweeps
+fn format_parameter_value(value: f64) -> String {
+    let rounded = (value * 1e9).round() / 1e9;
+    format!("{}", rounded)
+}
+
+/// An expression parsed into an operator tree, with a context that already
+/// holds the constants, functions and parameters. Each evaluation overwrites
+/// only the bound variables, so nothing is re-parsed or rebuilt per sample.
+#[derive(Clone)]
+struct CompiledExpression {
+    tree: Node<DefaultNumericTypes>,
+    context: HashMapContext<DefaultNumericTypes>,
+    /// Names bound on every evaluation, in the order the caller passes them
+    variables: Vec<String>,
+}
+
+impl CompiledExpression {
+    fn new(expression: &str, params: &[(String, f64)], variables: &[(&str, f64)]) -> Result<Self> {
+        let tree = build_operator_tree::<DefaultNumericTypes>(expression)
+            .map_err(|e| anyhow!("Failed to parse expression: {}", e))?;
+
+        // Later bindings win: constants, then user parameters, then sampled variables
+        let mut context = HashMapContext::<DefaultNumericTypes>::new();
+        math_functions::register(&mut context)?;
+        context.set_value("pi".into(), Value::Float(std::f64::consts::PI))?;
+        context.set_value("e".into(), Value::Float(std::f64::consts::E))?;
+        for (name, value) in params {
+            context.set_value(name.clone(), Value::Float(*value))?;
+        }
+
+        Ok(Self {
+            tree,
+            context,
+            variables: variables.iter().map(|(name, _)| name.to_string()).collect(),
+        })
+    }
+
+    fn binds(&self, variables: &[(&str, f64)]) -> bool {
+        self.variables.iter().map(String::as_str).eq(variables.iter().map(|(name, _)| *name))
+    }
+
+    fn evaluate(&mut self, variables: &[(&str, f64)]) -> Result<f64> {
+        for (name, value) in variables {
+            self.context.set_value(name.to_string(), Value::Float(*value))?;
+        }
+        match self.tree.eval_with_context(&self.context) {
+            Ok(value) => {
+                // Try to convert to f64 using the evalexpr API
+                if let Ok(num) = value.as_number() {
+                    Ok(num)
+                } else {
+                    Err(anyhow!("expected a number, got {}", value))
+                }
+            },
+            // Messages from our own functions, such as a wrong argument count
+            Err(EvalexprError::CustomMessage(message)) => Err(anyhow!(message)),
+            Err(e) => Err(anyhow!("{}", e)),
+        }
+    }
+}
 
 /// A mathematical function that can be evaluated and plotted
+#[derive(Clone)]
 pub struct Function {
     expression: String,
+    /// User-defined constants bound alongside the plotted variable
+    params: Vec<(String, f64)>,
+    /// Compiled on first evaluation and kept while the bound names stay the same
+    compiled: RefCell<Option<CompiledExpression>>,
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("expression", &self.expression)
+            .field("params", &self.params)
+            .finish()
+    }
 }
 
 impl Function {
     pub fn new(expression: &str) -> Self {
         Self {
             expression: expression.trim().to_string(),
+            params: Vec::new(),
+            compiled: RefCell::new(None),
         }
     }
 
-    /// Generate a dataset by evaluating the function over a range
+    /// Builder for binding user-defined parameters
+    pub fn with_params(mut self, params: &[(String, f64)]) -> Self {
+        self.params = params.to_vec();
+        self.compiled = RefCell::new(None);
+        self
+    }
+
+    /// Generate a dataset by evaluating the function over a range.
+    /// Poles, jumps and undefined stretches become gap markers so that no
+    /// line is drawn across them.
     pub fn generate_dataset(&self, x_min: f64, x_max: f64, num_points: Option<usize>) -> Result<Dataset> {
         let points_count = num_points.unwrap_or(200);
         
         if points_count == 0 {
             return Err(anyhow!("Number of points must be greater than 0"));
         }
+        self.validate(&["x"])?;
 
-        let mut legacy_points = Vec::new();
-        let step = if points_count == 1 {
+        let (samples, first_error) = self.sample_uniform(x_min, x_max, points_count);
+        self.build_dataset(samples, first_error)
+    }
+
+    /// Generate a dataset by refining where the curve bends. Starting from about
+    /// one sample per two columns, the interval whose midpoint strays furthest
+    /// from a straight segment, measured in screen rows, is split first, until
+    /// every segment is within half a row, intervals reach an eighth of a column,
+    /// or `max_evaluations` evaluations have been spent. The budget covers
+    /// sampling only: checking a suspicious pair for a pole or jump afterwards
+    /// takes up to 25 more evaluations, so that breaks are still found once the
+    /// budget has run out.
+    pub fn generate_adaptive_dataset(&self, x_min: f64, x_max: f64, max_evaluations: Option<usize>, sampling: &AdaptiveSampling) -> Result<Dataset> {
+        self.validate(&["x"])?;
+        let budget = max_evaluations.unwrap_or(200);
+        let initial_count = sampling.columns / 2 + 1;
+        if budget < initial_count * 2 {
+            // Not enough budget to refine anything; fall back to an even spread
+            return self.generate_dataset(x_min, x_max, Some(budget));
+        }
+
+        let (mut samples, first_error) = self.sample_uniform(x_min, x_max, initial_count);
+        let mut evaluations = initial_count;
+
+        // Screen scale from the initial pass: plot bounds follow the data
+        let (y_min, y_max) = samples.iter()
+            .filter_map(|(_, y)| *y)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| (lo.min(y), hi.max(y)));
+        let y_span = if y_max > y_min { y_max - y_min } else { 1.0 };
+        let row_height = y_span / sampling.rows as f64;
+        let min_width = (x_max - x_min).abs() / sampling.columns as f64 * MIN_INTERVAL_COLUMNS;
+
+        let mut queue = BinaryHeap::new();
+        for pair in samples.windows(2) {
+            queue.push(self.refinement(pair[0], pair[1], row_height));
+            evaluations += 1;
+        }
+
+        // Every evaluated midpoint becomes a sample; only the worst are split further
+        while let Some(refinement) = queue.pop() {
+            samples.push(refinement.mid);
+            if refinement.error <= MAX_ERROR_ROWS {
+                continue;
+            }
+
+            for (left, right) in [(refinement.left, refinement.mid), (refinement.mid, refinement.right)] {
+                if evaluations < budget && (right.0 - left.0).abs() > min_width {
+                    queue.push(self.refinement(left, right, row_height));
+                    evaluations += 1;
+                }
+            }
+        }
+
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.build_dataset(samples, first_error)
+    }
+
+    /// Evaluate the midpoint of an interval and score how badly a straight
+    /// segment would represent it. A change between defined and undefined is
+    /// always worth narrowing down.
+    fn refinement(&self, left: Sample, right: Sample, row_height: f64) -> Refinement {
+        let mid_x = (left.0 + right.0) / 2.0;
+        let mid = (mid_x, self.evaluate(mid_x).ok().filter(|y| f64::is_finite(*y)));
+
+        let error = match (left.1, mid.1, right.1) {
+            (Some(y0), Some(ym), Some(y1)) => (ym - (y0 + y1) / 2.0).abs() / row_height,
+            (None, None, None) => 0.0,
+            _ => f64::INFINITY,
+        };
+
+        Refinement { error, left, mid, right }
+    }
+
+    /// Evaluate the function at evenly spaced positions. Failed or infinite/NaN
+    /// evaluations are kept as `None` so they can break the line, and the
+    /// first error is returned alongside.
+    fn sample_uniform(&self, x_min: f64, x_max: f64, points_count: usize) -> (Vec<Sample>, Option<anyhow::Error>) {
+        let step = if points_count <= 1 {
             0.0
         } else {
             (x_max - x_min) / (points_count - 1) as f64
         };
 
-        for i in 0..points_count {
-            let x = x_min + i as f64 * step;
-            match self.evaluate(x) {
-                Ok(y) if y.is_finite() => {
-                    legacy_points.push(LegacyDataPoint { x, y });
+        let mut first_error = None;
+        let samples = (0..points_count)
+            .map(|i| {
+                let x = x_min + i as f64 * step;
+                (x, finite(self.evaluate(x), &mut first_error))
+            })
+            .collect();
+        (samples, first_error)
+    }
+
+    /// Turn samples in x order into a dataset, inserting gap markers at
+    /// undefined stretches and detected discontinuities
+    fn build_dataset(&self, samples: Vec<Sample>, first_error: Option<anyhow::Error>) -> Result<Dataset> {
+        if samples.iter().all(|(_, y)| y.is_none()) {
+            return Err(evaluation_failed("Function", first_error));
+        }
+
+        let jump_threshold = Self::jump_threshold(&samples);
+        let mut points = Vec::new();
+        let mut previous: Option<(f64, f64)> = None;
+        for (x, y) in samples {
+            match y {
+                Some(y) => {
+                    if let Some((prev_x, prev_y)) = previous {
+                        if self.is_discontinuity(prev_x, prev_y, x, y, jump_threshold) {
+                            points.push(DataPoint::gap((prev_x + x) / 2.0));
+                        }
+                    }
+                    points.push(DataPoint::Numeric(x, y));
+                    previous = Some((x, y));
                 }
-                _ => {
-                    // Skip points where function evaluation fails or returns infinite/NaN
-                    continue;
+                None => {
+                    if previous.is_some() {
+                        points.push(DataPoint::gap(x));
+                    }
+                    previous = None;
                 }
             }
         }
 
-        if legacy_points.is_empty() {
-            return Err(anyhow!("Function evaluation failed for all points in range"));
-        }
+        let dataset = Dataset::new_numeric(
+            points,
+            "x".to_string(),
+            format!("f(x) = {}", self.expression),
+        );
+        Ok(dataset.with_series_label(&self.expression))
+    }
 
-        // Convert legacy points to new format
-        let points: Vec<DataPoint> = legacy_points.into_iter()
-            .map(|p| DataPoint::Numeric(p.x, p.y))
+    /// Change between neighbouring samples above which a pair is checked for a
+    /// discontinuity: a multiple of the typical (median) change
+    fn jump_threshold(samples: &[Sample]) -> f64 {
+        let mut deltas: Vec<f64> = samples.windows(2)
+            .filter_map(|pair| Some((pair[1].1? - pair[0].1?).abs()))
             .collect();
+        if deltas.is_empty() {
+            return f64::INFINITY;
+        }
 
-        Ok(Dataset {
-            points,
-            x_label: "x".to_string(),
-            y_label: format!("f(x) = {}", self.expression),
-            is_categorical: false,
-            categories: Vec::new(),
-        })
+        deltas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        deltas[deltas.len() / 2] * JUMP_FACTOR
+    }
+
+    /// Decide whether the function breaks between two finite samples.
+    ///
+    /// Pairs that change sign, jump by more than the threshold, or whose midpoint
+    /// overshoots both ends are bisected towards the larger change. A continuous
+    /// function's change shrinks with the interval; at a pole it grows and at a
+    /// jump it stays put, and a non-finite value on the way is a break outright.
+    fn is_discontinuity(&self, x0: f64, y0: f64, x1: f64, y1: f64, jump_threshold: f64) -> bool {
+        let finite = |x: f64| self.evaluate(x).ok().filter(|y| f64::is_finite(*y));
+
+        let Some(y_mid) = finite((x0 + x1) / 2.0) else {
+            return true;
+        };
+
+        let (low, high) = (y0.min(y1), y0.max(y1));
+        let slack = (high - low) + jump_threshold;
+        let sign_change = y0 * y1 < 0.0;
+        let jump = high - low > jump_threshold;
+        let overshoot = y_mid < low - slack || y_mid > high + slack;
+        if !(sign_change || jump || overshoot) {
+            return false;
+        }
+
+        let initial_change = (high - low).max(f64::MIN_POSITIVE);
+        let (mut left, mut right) = ((x0, y0), (x1, y1));
+        for _ in 0..DISCONTINUITY_BISECTIONS {
+            let mid_x = (left.0 + right.0) / 2.0;
+            let Some(mid_y) = finite(mid_x) else {
+                return true;
+            };
+
+            if (mid_y - left.1).abs() >= (right.1 - mid_y).abs() {
+                right = (mid_x, mid_y);
+            } else {
+                left = (mid_x, mid_y);
+            }
+        }
+
+        (right.1 - left.1).abs() > initial_change * 0.5
+    }
+
+    /// Check the expression's syntax and names once, before sampling, so that
+    /// typos are reported with their position rather than as a blank plot.
+    /// `variables` are the names the caller binds, alongside any parameters.
+    pub fn validate(&self, variables: &[&str]) -> Result<()> {
+        let functions: Vec<&str> = math_functions::names().collect();
+        let mut known: Vec<&str> = self.params.iter().map(|(name, _)| name.as_str()).collect();
+        known.extend(variables);
+        expression::validate(&self.expression, &functions, &known)
     }
 
     /// Evaluate the function at a given x value
-    fn evaluate(&self, x: f64) -> Result<f64> {
-        // Pre-process expression to add simple function aliases
-        let expression = self.add_function_aliases(&self.expression);
-        
-        // Use evalexpr to evaluate the expression with x variable
-        let mut context = HashMapContext::<evalexpr::DefaultNumericTypes>::new();
-        context.set_value("x".into(), Value::Float(x))?;
-        context.set_value("pi".into(), Value::Float(std::f64::consts::PI))?;
-        context.set_value("e".into(), Value::Float(std::f64::consts::E))?;
+    pub fn evaluate(&self, x: f64) -> Result<f64> {
+        self.evaluate_with(&[("x", x)])
+    }
+
+    /// Evaluate the function with the given variable bindings (e.g. `t` for parametric curves)
+    pub fn evaluate_with(&self, variables: &[(&str, f64)]) -> Result<f64> {
+        let mut compiled = self.compiled.borrow_mut();
+        if !compiled.as_ref().is_some_and(|c| c.binds(variables)) {
+            *compiled = Some(CompiledExpression::new(&self.expression, &self.params, variables)?);
+        }
+        compiled.as_mut().unwrap().evaluate(variables)
+    }
+
+}
+
+/// A parametric curve (x(t), y(t)) traced in order of increasing t
+pub struct ParametricCurve {
+    x: Function,
+    y: Function,
+}
+
+impl ParametricCurve {
+    pub fn new(x_expression: &str, y_expression: &str) -> Self {
+        Self {
+            x: Function::new(x_expression),
+            y: Function::new(y_expression),
+        }
+    }
+
+    /// Builder for binding user-defined parameters in both coordinates
+    pub fn with_params(self, params: &[(String, f64)]) -> Self {
+        Self {
+            x: self.x.with_params(params),
+            y: self.y.with_params(params),
+        }
+    }
+
+    /// Generate a dataset by sampling t evenly over a range. Points keep their
+    /// generation order so the curve is drawn along t rather than sorted by x.
+    pub fn generate_dataset(&self, t_min: f64, t_max: f64, num_points: Option<usize>) -> Result<Dataset> {
+        let points_count = num_points.unwrap_or(200);
         
-        match eval_with_context(&expression, &context) {
-            Ok(value) => {
-                // Try to convert to f64 using the evalexpr API
-                if let Ok(num) = value.as_float() {
-                    Ok(num)
-                } else {
-                    Err(anyhow!("Expression did not evaluate to a number: {:?}", value))
-                }
-            },
-            Err(e) => Err(anyhow!("Failed to evaluate expression: {}", e)),
-        }
-    }
-
-    /// Add simple function aliases (sin -> math::sin, etc.)
-    fn add_function_aliases(&self, expr: &str) -> String {
-        let aliases = &[
-            ("sin(", "math::sin("),
-            ("cos(", "math::cos("),
-            ("tan(", "math::tan("),
-            ("exp(", "math::exp("),
-            ("ln(", "math::ln("),
-            ("log(", "math::log10("),
-            ("log10(", "math::log10("),
-            ("log2(", "math::log2("),
-            ("sqrt(", "math::sqrt("),
-            ("abs(", "math::abs("),
-            ("asin(", "math::asin("),
-            ("acos(", "math::acos("),
-            ("atan(", "math::atan("),
-        ];
+        if points_count == 0 {
+            return Err(anyhow!("Number of points must be greater than 0"));
+        }
+        self.x.validate(&["t"])?;
+        self.y.validate(&["t"])?;
+
+        let step = if points_count == 1 {
+            0.0
+        } else {
+            (t_max - t_min) / (points_count - 1) as f64
+        };
+
+        let mut points = Vec::new();
+        let mut first_error = None;
+        for i in 0..points_count {
+            let t = t_min + i as f64 * step;
+            let x = finite(self.x.evaluate_with(&[("t", t)]), &mut first_error);
+            let y = finite(self.y.evaluate_with(&[("t", t)]), &mut first_error);
+            if let (Some(x), Some(y)) = (x, y) {
+                points.push(DataPoint::Numeric(x, y));
+            }
+        }
+
+        if points.is_empty() {
+            return Err(evaluation_failed("Parametric", first_error));
+        }
+
+        let dataset = Dataset::new_numeric(
+            points,
+            format!("x(t) = {}", self.x.expression),
+            format!("y(t) = {}", self.y.expression),
+        );
+        let label = format!("({}, {})", self.x.expression, self.y.expression);
+        Ok(dataset.with_series_label(&label).with_preserve_order(true))
+    }
+}
+
+/// A polar curve r(theta), traced in order of increasing theta
+pub struct PolarCurve {
+    r: Function,
+}
+
+impl PolarCurve {
+    pub fn new(expression: &str) -> Self {
+        Self {
+            r: Function::new(expression),
+        }
+    }
+
+    /// Builder for binding user-defined parameters
+    pub fn with_params(self, params: &[(String, f64)]) -> Self {
+        Self {
+            r: self.r.with_params(params),
+        }
+    }
+
+    /// Generate a polar dataset of (theta, r) points by sampling theta evenly
+    /// over a range in radians
+    pub fn generate_dataset(&self, theta_min: f64, theta_max: f64, num_points: Option<usize>) -> Result<Dataset> {
+        let points_count = num_points.unwrap_or(200);
         
-        let mut result = expr.to_string();
-        for (alias, full_name) in aliases {
-            result = result.replace(alias, full_name);
+        if points_count == 0 {
+            return Err(anyhow!("Number of points must be greater than 0"));
         }
-        result
+        self.r.validate(&["theta"])?;
+
+        let step = if points_count == 1 {
+            0.0
+        } else {
+            (theta_max - theta_min) / (points_count - 1) as f64
+        };
+
+        let mut points = Vec::new();
+        let mut first_error = None;
+        for i in 0..points_count {
+            let theta = theta_min + i as f64 * step;
+            if let Some(r) = finite(self.r.evaluate_with(&[("theta", theta)]), &mut first_error) {
+                points.push(DataPoint::Numeric(theta, r));
+            }
+        }
+
+        if points.is_empty() {
+            return Err(evaluation_failed("Polar", first_error));
+        }
+
+        let dataset = Dataset::new_numeric(
+            points,
+            "theta".to_string(),
+            format!("r(theta) = {}", self.r.expression),
+        );
+        Ok(dataset
+            .with_series_label(&self.r.expression)
+            .with_preserve_order(true)
+            .with_polar(true))
     }
+}
+
+/// The value of an evaluation if it succeeded with a finite result, keeping
+/// the first error seen
+fn finite(result: Result<f64>, first_error: &mut Option<anyhow::Error>) -> Option<f64> {
+    match result {
+        Ok(value) => Some(value).filter(|value| f64::is_finite(*value)),
+        Err(e) => {
+            first_error.get_or_insert(e);
+            None
+        }
+    }
+}
 
+/// The error for a curve without a single finite point. An evaluation error
+/// such as a wrong argument count fails everywhere alike, so the first one is
+/// the cause; without one, every value was infinite or NaN.
+fn evaluation_failed(kind: &str, first_error: Option<anyhow::Error>) -> anyhow::Error {
+    match first_error {
+        Some(error) => anyhow!("{} evaluation failed: {}", kind, error),
+        None => anyhow!("{} evaluation failed for all points in range", kind),
+    }
 }
 
 /// Detect intelligent default range for different function types
@@ -121,10 +654,6 @@ pub fn detect_range(expression: &str) -> (f64, f64) {
         (0.1, 10.0)
     } else if expr.contains("sqrt") {
         (0.0, 10.0)
-    } else if expr.contains("tan") {
-        (-1.5, 1.5)
-    } else if expr.contains("1/x") || expr.contains("/x") {
-        (-10.0, 10.0)
     } else {
         (-10.0, 10.0)
     }
@@ -162,10 +691,254 @@ mod tests {
         assert_eq!(dataset.points.len(), 5);
     }
 
+    #[test]
+    fn test_validation_knows_parameters_and_variables() {
+        let func = Function::new("a*sin(t)");
+        assert!(func.validate(&["t"]).unwrap_err().to_string().starts_with("Unknown variable 'a'"));
+        let func = func.with_params(&[("a".to_string(), 2.0)]);
+        assert!(func.validate(&["t"]).is_ok());
+        // Plotting over x does not bind t
+        let error = func.generate_dataset(0.0, 1.0, Some(3)).unwrap_err().to_string();
+        assert!(error.starts_with("Unknown variable 't'"));
+    }
+
+    #[test]
+    fn test_evaluation_errors_name_the_cause() {
+        let error = |result: Result<Dataset>| result.unwrap_err().to_string();
+        let sampling = AdaptiveSampling::new(60, 20);
+        assert_eq!(
+            error(Function::new("pow(x)").generate_dataset(0.0, 1.0, Some(10))),
+            "Function evaluation failed: pow() takes 2 arguments, got 1"
+        );
+        assert_eq!(
+            error(Function::new("max()").generate_adaptive_dataset(0.0, 1.0, None, &sampling)),
+            "Function evaluation failed: max() takes at least 1 argument, got 0"
+        );
+        assert_eq!(
+            error(ParametricCurve::new("t", "t > 1").generate_dataset(0.0, 1.0, Some(10))),
+            "Parametric evaluation failed: expected a number, got false"
+        );
+        assert_eq!(
+            error(PolarCurve::new("pow(theta)").generate_dataset(0.0, 1.0, Some(10))),
+            "Polar evaluation failed: pow() takes 2 arguments, got 1"
+        );
+        // Without an error, every value was out of the domain
+        assert_eq!(
+            error(Function::new("sqrt(-1 - x^2)").generate_dataset(0.0, 1.0, Some(10))),
+            "Function evaluation failed for all points in range"
+        );
+    }
+
+    #[test]
+    fn test_compiled_expression_follows_bindings() {
+        let func = Function::new("x + 2*t");
+        assert_eq!(func.evaluate_with(&[("x", 1.0), ("t", 1.0)]).unwrap(), 3.0);
+        assert_eq!(func.evaluate_with(&[("x", 2.0), ("t", 5.0)]).unwrap(), 12.0);
+        // Different names recompile; a name left unbound is an error, not a stale value
+        assert!(func.evaluate(1.0).is_err());
+        assert_eq!(func.evaluate_with(&[("t", 1.0), ("x", 0.0)]).unwrap(), 2.0);
+
+        let func = Function::new("a*x").with_params(&[("a".to_string(), 2.0)]);
+        assert_eq!(func.evaluate(3.0).unwrap(), 6.0);
+        let func = func.with_params(&[("a".to_string(), 5.0)]);
+        assert_eq!(func.evaluate(3.0).unwrap(), 15.0);
+    }
+
+    /// Compiled evaluation against parsing the expression and building a
+    /// context for every sample. Run with
+    /// `cargo test --release bench_evaluation -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_evaluation_throughput() {
+        use std::time::Instant;
+
+        let expression = "sin(x)^2 + normal_pdf(x, 0, 2) * exp(-x / 10)";
+        let xs: Vec<f64> = (0..20_000).map(|i| -10.0 + i as f64 * 0.001).collect();
+
+        let start = Instant::now();
+        let mut reference = 0.0;
+        for x in &xs {
+            let mut context = HashMapContext::<DefaultNumericTypes>::new();
+            math_functions::register(&mut context).unwrap();
+            context.set_value("x".into(), Value::Float(*x)).unwrap();
+            reference += eval_with_context(expression, &context).unwrap().as_number().unwrap();
+        }
+        let uncompiled = start.elapsed();
+
+        let func = Function::new(expression);
+        let start = Instant::now();
+        let total: f64 = xs.iter().map(|x| func.evaluate(*x).unwrap()).sum();
+        let compiled = start.elapsed();
+
+        let rate = |elapsed: std::time::Duration| xs.len() as f64 / elapsed.as_secs_f64();
+        println!(
+            "per-sample parse: {:>10.0} evals/s\ncompiled:         {:>10.0} evals/s ({:.1}x)",
+            rate(uncompiled),
+            rate(compiled),
+            uncompiled.as_secs_f64() / compiled.as_secs_f64(),
+        );
+        assert!((total - reference).abs() < 1e-6);
+        assert!(compiled < uncompiled);
+    }
+
     #[test]
     fn test_range_detection() {
         assert_eq!(detect_range("sin(x)"), (-10.0, 10.0));
         assert_eq!(detect_range("exp(x)"), (-5.0, 5.0));
         assert_eq!(detect_range("ln(x)"), (0.1, 10.0));
     }
-}
\ No newline at end of file
+
+    fn gap_positions(dataset: &Dataset) -> Vec<f64> {
+        dataset.points.iter()
+            .filter(|p| p.is_gap())
+            .filter_map(DataPoint::x_numeric)
+            .collect()
+    }
+
+    #[test]
+    fn test_poles_become_gaps() {
+        let tan = Function::new("tan(x)").generate_dataset(-3.0, 3.0, Some(100)).unwrap();
+        let gaps = gap_positions(&tan);
+        assert_eq!(gaps.len(), 2);
+        assert!((gaps[0] + std::f64::consts::FRAC_PI_2).abs() < 0.1);
+        assert!((gaps[1] - std::f64::consts::FRAC_PI_2).abs() < 0.1);
+
+        let reciprocal = Function::new("1/x").generate_dataset(-5.0, 5.0, Some(50)).unwrap();
+        let gaps = gap_positions(&reciprocal);
+        assert_eq!(gaps.len(), 1);
+        assert!(gaps[0].abs() < 0.2);
+    }
+
+    #[test]
+    fn test_jumps_break_but_continuous_functions_do_not() {
+        let floor = Function::new("floor(x)").generate_dataset(0.5, 3.5, Some(31)).unwrap();
+        assert_eq!(gap_positions(&floor).len(), 3);
+
+        // Steep and sign-changing, but continuous
+        for expression in ["sin(x)", "x^3", "sin(20*x)"] {
+            let dataset = Function::new(expression).generate_dataset(-3.0, 3.0, Some(200)).unwrap();
+            assert!(gap_positions(&dataset).is_empty(), "unexpected gap in {}", expression);
+        }
+    }
+
+    #[test]
+    fn test_undefined_stretch_is_a_gap() {
+        let dataset = Function::new("sqrt(1 - x^2)").generate_dataset(-2.0, 2.0, Some(41)).unwrap();
+        let gaps = gap_positions(&dataset);
+
+        // Undefined before -1 adds nothing; leaving the domain at +1 breaks the line once
+        assert_eq!(gaps.len(), 1);
+        assert!(gaps[0] > 1.0);
+    }
+
+    fn sample_count(dataset: &Dataset) -> usize {
+        dataset.points.iter().filter(|p| !p.is_gap()).count()
+    }
+
+    #[test]
+    fn test_adaptive_spends_less_on_smooth_functions() {
+        let sampling = AdaptiveSampling::new(80, 24);
+        let dataset = Function::new("sin(x)")
+            .generate_adaptive_dataset(-10.0, 10.0, Some(2000), &sampling)
+            .unwrap();
+
+        let count = sample_count(&dataset);
+        assert!(count > 41 && count < 200, "sin(x) took {} samples", count);
+    }
+
+    #[test]
+    fn test_adaptive_refines_oscillation_within_budget() {
+        let sampling = AdaptiveSampling::new(80, 24);
+        let dataset = Function::new("sin(1/x)")
+            .generate_adaptive_dataset(0.01, 1.0, Some(1000), &sampling)
+            .unwrap();
+
+        assert!(sample_count(&dataset) <= 1000);
+
+        // Samples crowd into the first tenth of the range, where the oscillation is fastest
+        let near_zero = dataset.points.iter()
+            .filter(|p| !p.is_gap() && p.x_numeric().unwrap() < 0.1)
+            .count();
+        assert!(near_zero > sample_count(&dataset) / 4);
+
+        // Points stay in x order for drawing
+        let xs: Vec<f64> = dataset.points.iter().filter_map(DataPoint::x_numeric).collect();
+        assert!(xs.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_adaptive_falls_back_to_uniform_on_small_budget() {
+        let sampling = AdaptiveSampling::new(80, 24);
+        let dataset = Function::new("x").generate_adaptive_dataset(0.0, 1.0, Some(20), &sampling).unwrap();
+        assert_eq!(dataset.points.len(), 20);
+    }
+
+    #[test]
+    fn test_parametric_circle_keeps_t_order() {
+        let curve = ParametricCurve::new("cos(t)", "sin(t)");
+        let dataset = curve.generate_dataset(0.0, 2.0 * std::f64::consts::PI, Some(9)).unwrap();
+        
+        assert!(dataset.preserve_order);
+        assert_eq!(dataset.points.len(), 9);
+        
+        // Quarter turns visit (1, 0), (0, 1), (-1, 0), (0, -1) in that order
+        let quarter_turns: Vec<(f64, f64)> = dataset.points.iter()
+            .step_by(2)
+            .map(|p| match p {
+                DataPoint::Numeric(x, y) => (f64::round(*x), f64::round(*y)),
+                DataPoint::Categorical(_, _) => panic!("Expected numeric point"),
+            })
+            .collect();
+        assert_eq!(&quarter_turns[..4], &[(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)]);
+    }
+
+    #[test]
+    fn test_polar_curve_samples_theta() {
+        let curve = PolarCurve::new("1 + cos(theta)");
+        let dataset = curve.generate_dataset(0.0, std::f64::consts::PI, Some(3)).unwrap();
+        
+        assert!(dataset.is_polar);
+        assert_eq!(dataset.y_label, "r(theta) = 1 + cos(theta)");
+        assert!((dataset.points[0].y() - 2.0).abs() < 1e-10);
+        assert!((dataset.points[1].y() - 1.0).abs() < 1e-10);
+        assert!(dataset.points[2].y().abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_parse_parameters() {
+        assert_eq!(Parameter::parse("a=2").unwrap().values, vec![2.0]);
+        assert_eq!(Parameter::parse("b = -0.5").unwrap().values, vec![-0.5]);
+        assert_eq!(Parameter::parse("k=1..5:5").unwrap().values, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(Parameter::parse("k=-1..1").unwrap().values.len(), DEFAULT_SWEEP_COUNT);
+
+        assert!(Parameter::parse("a").is_err());
+        assert!(Parameter::parse("x=2").is_err());
+        assert!(Parameter::parse("2a=1").is_err());
+        assert!(Parameter::parse("a=1..2:0").is_err());
+        assert!(Parameter::parse("a=two").is_err());
+    }
+
+    #[test]
+    fn test_parameters_are_bound() {
+        let params = vec![("a".to_string(), 2.0), ("b".to_string(), 0.5)];
+        let func = Function::new("a*x + b").with_params(&params);
+        assert_eq!(func.evaluate(3.0).unwrap(), 6.5);
+    }
+
+    #[test]
+    fn test_parameter_sets_label_sweeps() {
+        let parameters = vec![
+            Parameter::parse("a=0..1:3").unwrap(),
+            Parameter::parse("b=2").unwrap(),
+        ];
+        let sets = parameter_sets(&parameters);
+
+        assert_eq!(sets.len(), 3);
+        assert_eq!(sets[1].bindings, vec![("a".to_string(), 0.5), ("b".to_string(), 2.0)]);
+        assert_eq!(sets[1].series_label("a*x"), "a*x (a=0.5)");
+
+        let fixed = parameter_sets(&parameters[1..]);
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].series_label("b*x"), "b*x");
+    }
+}
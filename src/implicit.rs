@@ -0,0 +1,283 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use crate::data::{DataPoint, Dataset};
+use crate::function::Function;
+
+/// Halvings used to place a crossing along a grid edge
+const EDGE_BISECTIONS: usize = 24;
+
+/// A grid edge, identified by its lower-left corner and direction, on which
+/// the contour crosses zero. Neighbouring cells share edges, which is how
+/// segments are joined into curves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Edge {
+    /// From corner (i, j) to (i + 1, j)
+    Horizontal(usize, usize),
+    /// From corner (i, j) to (i, j + 1)
+    Vertical(usize, usize),
+}
+
+/// The curve F(x, y) = 0, written as an equation `lhs = rhs` or as F alone
+pub struct ImplicitCurve {
+    equation: String,
+    lhs: Function,
+    rhs: Option<Function>,
+}
+
+impl ImplicitCurve {
+    pub fn new(equation: &str) -> Result<Self> {
+        let (lhs, rhs) = match split_equation(equation)? {
+            Some((lhs, rhs)) => (Function::new(lhs), Some(Function::new(rhs))),
+            None => (Function::new(equation), None),
+        };
+        Ok(Self {
+            equation: equation.trim().to_string(),
+            lhs,
+            rhs,
+        })
+    }
+
+    /// Builder for binding user-defined parameters
+    pub fn with_params(self, params: &[(String, f64)]) -> Self {
+        Self {
+            equation: self.equation,
+            lhs: self.lhs.with_params(params),
+            rhs: self.rhs.map(|rhs| rhs.with_params(params)),
+        }
+    }
+
+    /// F(x, y) = lhs - rhs, or `None` where either side is undefined
+    fn evaluate(&self, x: f64, y: f64) -> Option<f64> {
+        let variables = [("x", x), ("y", y)];
+        let lhs = self.lhs.evaluate_with(&variables).ok()?;
+        let rhs = match &self.rhs {
+            Some(rhs) => rhs.evaluate_with(&variables).ok()?,
+            None => 0.0,
+        };
+        Some(lhs - rhs).filter(|v| v.is_finite())
+    }
+
+    /// Trace the zero contour over a grid of `cells` by `cells` squares with
+    /// marching squares. The curve comes back in drawing order, one run of
+    /// points per connected piece, separated by gap markers.
+    pub fn generate_dataset(&self, (x_min, x_max): (f64, f64), (y_min, y_max): (f64, f64), cells: usize) -> Result<Dataset> {
+        if cells == 0 {
+            return Err(anyhow!("Number of points must be greater than 0"));
+        }
+        self.lhs.validate(&["x", "y"])?;
+        if let Some(rhs) = &self.rhs {
+            rhs.validate(&["x", "y"])?;
+        }
+
+        let xs: Vec<f64> = (0..=cells).map(|i| x_min + (x_max - x_min) * i as f64 / cells as f64).collect();
+        let ys: Vec<f64> = (0..=cells).map(|j| y_min + (y_max - y_min) * j as f64 / cells as f64).collect();
+        let grid: Vec<Vec<Option<f64>>> = xs.iter()
+            .map(|x| ys.iter().map(|y| self.evaluate(*x, *y)).collect())
+            .collect();
+        if grid.iter().flatten().all(Option::is_none) {
+            return Err(anyhow!("Implicit evaluation failed for all points in range"));
+        }
+
+        // Where the contour crosses each edge, found by bisection along it
+        let mut crossings: HashMap<Edge, (f64, f64)> = HashMap::new();
+        let mut crossing = |edge: Edge| -> Option<(f64, f64)> {
+            if let Some(point) = crossings.get(&edge) {
+                return Some(*point);
+            }
+            let ((x0, y0, f0), (x1, y1, f1)) = match edge {
+                Edge::Horizontal(i, j) => ((xs[i], ys[j], grid[i][j]?), (xs[i + 1], ys[j], grid[i + 1][j]?)),
+                Edge::Vertical(i, j) => ((xs[i], ys[j], grid[i][j]?), (xs[i], ys[j + 1], grid[i][j + 1]?)),
+            };
+            let along = |t: f64| (x0 + t * (x1 - x0), y0 + t * (y1 - y0));
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..EDGE_BISECTIONS {
+                let mid = (low + high) / 2.0;
+                let (x, y) = along(mid);
+                let value = self.evaluate(x, y)?;
+                // Same sign test as the cell classification: positive or not
+                if (value > 0.0) == (f0 > 0.0) {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+
+            // A sign change that grows rather than shrinks under bisection is a pole
+            let point = along((low + high) / 2.0);
+            let value = self.evaluate(point.0, point.1)?;
+            if value.abs() > f0.abs().max(f1.abs()) {
+                return None;
+            }
+            crossings.insert(edge, point);
+            Some(point)
+        };
+
+        let mut segments = Vec::new();
+        for i in 0..cells {
+            for j in 0..cells {
+                let (Some(a), Some(b), Some(c), Some(d)) =
+                    (grid[i][j], grid[i + 1][j], grid[i + 1][j + 1], grid[i][j + 1])
+                else {
+                    continue;
+                };
+                let center = || self.evaluate((xs[i] + xs[i + 1]) / 2.0, (ys[j] + ys[j + 1]) / 2.0);
+                for (from, to) in cell_segments(i, j, [a, b, c, d], center) {
+                    if let (Some(_), Some(_)) = (crossing(from), crossing(to)) {
+                        segments.push((from, to));
+                    }
+                }
+            }
+        }
+
+        let mut points = Vec::new();
+        for chain in join_segments(&segments) {
+            if !points.is_empty() {
+                let (x, _) = crossings[&chain[0]];
+                points.push(DataPoint::gap(x));
+            }
+            points.extend(chain.iter().map(|edge| {
+                let (x, y) = crossings[edge];
+                DataPoint::Numeric(x, y)
+            }));
+        }
+
+        let dataset = Dataset::new_numeric(points, "x".to_string(), self.equation.clone());
+        Ok(dataset
+            .with_series_label(&self.equation)
+            .with_preserve_order(true))
+    }
+}
+
+/// Split "lhs = rhs" at its single `=`, ignoring `==`, `<=`, `>=` and `!=`
+fn split_equation(equation: &str) -> Result<Option<(&str, &str)>> {
+    let bytes = equation.as_bytes();
+    let positions: Vec<usize> = (0..bytes.len())
+        .filter(|&i| bytes[i] == b'=')
+        .filter(|&i| i == 0 || !b"<>!=".contains(&bytes[i - 1]))
+        .filter(|&i| bytes.get(i + 1) != Some(&b'='))
+        .collect();
+
+    match positions[..] {
+        [] => Ok(None),
+        [i] => Ok(Some((&equation[..i], &equation[i + 1..]))),
+        _ => Err(anyhow!("Implicit equation must have a single '=', got: {}", equation)),
+    }
+}
+
+/// Contour segments through one cell, as pairs of crossed edges. Corners are
+/// given anticlockwise from the lower left; the saddle cases, where opposite
+/// corners share a sign, are resolved by the value at the cell centre.
+fn cell_segments(i: usize, j: usize, corners: [f64; 4], center: impl Fn() -> Option<f64>) -> Vec<(Edge, Edge)> {
+    let bottom = Edge::Horizontal(i, j);
+    let right = Edge::Vertical(i + 1, j);
+    let top = Edge::Horizontal(i, j + 1);
+    let left = Edge::Vertical(i, j);
+
+    let case = corners.iter()
+        .enumerate()
+        .fold(0, |case, (bit, value)| if *value > 0.0 { case | (1 << bit) } else { case });
+
+    match case {
+        0 | 15 => vec![],
+        1 | 14 => vec![(left, bottom)],
+        2 | 13 => vec![(bottom, right)],
+        3 | 12 => vec![(left, right)],
+        4 | 11 => vec![(right, top)],
+        6 | 9 => vec![(bottom, top)],
+        7 | 8 => vec![(left, top)],
+        5 | 10 => {
+            // Lower-left and upper-right share a sign: the centre decides
+            // whether they are joined through the middle
+            let center_positive = center().is_some_and(|value| value > 0.0);
+            if center_positive == (case == 5) {
+                vec![(left, top), (bottom, right)]
+            } else {
+                vec![(left, bottom), (right, top)]
+            }
+        }
+        _ => unreachable!("marching squares case out of range"),
+    }
+}
+
+/// Join segments that share an edge into chains, open curves first, then loops
+fn join_segments(segments: &[(Edge, Edge)]) -> Vec<Vec<Edge>> {
+    let mut neighbours: HashMap<Edge, Vec<Edge>> = HashMap::new();
+    for (a, b) in segments {
+        neighbours.entry(*a).or_default().push(*b);
+        neighbours.entry(*b).or_default().push(*a);
+    }
+
+    // Visit edges in segment order so the output does not depend on hashing
+    let mut starts: Vec<Edge> = segments.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    starts.sort_by_key(|edge| neighbours[edge].len() != 1);
+
+    let mut chains = Vec::new();
+    for start in starts {
+        if neighbours[&start].is_empty() {
+            continue;
+        }
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(next) = neighbours.get_mut(&current).and_then(Vec::pop) {
+            let back = neighbours.get_mut(&next).unwrap();
+            if let Some(index) = back.iter().position(|edge| *edge == current) {
+                back.swap_remove(index);
+            }
+            chain.push(next);
+            current = next;
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(dataset: &Dataset) -> Vec<&[DataPoint]> {
+        dataset.points.split(DataPoint::is_gap).collect()
+    }
+
+    #[test]
+    fn test_unit_circle_is_one_closed_loop() {
+        let curve = ImplicitCurve::new("x^2 + y^2 = 1").unwrap();
+        let dataset = curve.generate_dataset((-2.0, 2.0), (-2.0, 2.0), 40).unwrap();
+
+        let loops = pieces(&dataset);
+        assert_eq!(loops.len(), 1);
+        for point in loops[0] {
+            let radius = point.x_numeric().unwrap().hypot(point.y());
+            assert!((radius - 1.0).abs() < 0.02, "radius {}", radius);
+        }
+        // Closed: the walk returns to where it started
+        let (first, last) = (&loops[0][0], loops[0].last().unwrap());
+        assert_eq!((first.x_numeric(), first.y()), (last.x_numeric(), last.y()));
+        assert_eq!(dataset.y_label, "x^2 + y^2 = 1");
+        assert!(dataset.preserve_order);
+    }
+
+    #[test]
+    fn test_separate_pieces_and_poles() {
+        // A hyperbola has two branches
+        let hyperbola = ImplicitCurve::new("x^2 - y^2 = 1").unwrap();
+        let dataset = hyperbola.generate_dataset((-3.0, 3.0), (-3.0, 3.0), 30).unwrap();
+        assert_eq!(pieces(&dataset).len(), 2);
+
+        // y = tan(x) changes sign across its poles, which must not be joined up
+        let tangent = ImplicitCurve::new("y = tan(x)").unwrap();
+        let dataset = tangent.generate_dataset((-3.0, 3.0), (-3.0, 3.0), 60).unwrap();
+        assert_eq!(pieces(&dataset).len(), 3);
+        assert!(dataset.points.iter()
+            .filter(|p| !p.is_gap())
+            .all(|p| (p.y() - p.x_numeric().unwrap().tan()).abs() < 0.2));
+    }
+
+    #[test]
+    fn test_split_equation() {
+        assert_eq!(split_equation("x^2 + y^2 = 1").unwrap(), Some(("x^2 + y^2 ", " 1")));
+        assert_eq!(split_equation("x*y - 1").unwrap(), None);
+        assert_eq!(split_equation("if(x >= 0, x, -x) = y").unwrap(), Some(("if(x >= 0, x, -x) ", " y")));
+        assert!(split_equation("x = y = 1").is_err());
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::{Result, anyhow};
+use std::fs::File;
+use flate2::read::MultiGzDecoder;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::time::Duration;
+use crate::delimited::Delimiter;
+
+/// Source name that reads data from standard input instead of a file
+pub const STDIN_SOURCE: &str = "-";
+
+/// Leading bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Leading bytes of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Field values that stand for a missing observation, compared case-insensitively
+const MISSING_VALUES: [&str; 4] = ["", "na", "nan", "null"];
+
+/// What to do with a row whose values cannot be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BadRowPolicy {
+    /// Stop with an error naming the file, line and column
+    #[default]
+    Error,
+    /// Drop the row silently
+    Skip,
+    /// Drop the row and print a warning to stderr
+    Warn,
+}
+
+impl BadRowPolicy {
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "error" => Ok(BadRowPolicy::Error),
+            "skip" => Ok(BadRowPolicy::Skip),
+            "warn" => Ok(BadRowPolicy::Warn),
+            _ => Err(anyhow!("--on-bad-row must be error, skip or warn, got: {}", text)),
+        }
+    }
+
+    /// Apply the policy to a bad row: an error stops reading, otherwise the
+    /// caller drops the row and carries on
+    pub fn handle(&self, error: anyhow::Error) -> Result<()> {
+        match self {
+            BadRowPolicy::Error => Err(error),
+            BadRowPolicy::Skip => Ok(()),
+            BadRowPolicy::Warn => {
+                eprintln!("Warning: {}; row skipped", error);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How much of a followed source is plotted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    /// The latest lines
+    Rows(usize),
+    /// Lines that arrived within the period; on a time axis, also points
+    /// no older than the period before the latest timestamp
+    Duration(Duration),
+}
+
+impl Window {
+    /// Parse a row count such as "500" or a duration such as "30s", "5m", "2h" or "1d"
+    pub fn parse(text: &str) -> Result<Self> {
+        let error = || anyhow!(
+            "--window must be a row count such as 500 or a duration such as 30s, 5m or 2h, got: {}",
+            text
+        );
+        if let Ok(rows) = text.parse::<usize>() {
+            return if rows > 0 { Ok(Window::Rows(rows)) } else { Err(error()) };
+        }
+
+        let split = text.find(|c: char| c.is_ascii_alphabetic()).ok_or_else(error)?;
+        let (amount, unit) = text.split_at(split);
+        let amount: f64 = amount.trim().parse().map_err(|_| error())?;
+        let seconds = match unit {
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "d" => 86400.0,
+            _ => return Err(error()),
+        };
+        if !(amount > 0.0 && amount.is_finite()) {
+            return Err(error());
+        }
+        Ok(Window::Duration(Duration::from_secs_f64(amount * seconds)))
+    }
+}
+
+/// Compression wrapped around a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Recognise compression by the magic bytes at the start of the data, so
+    /// stdin and misnamed files work. The file extension only decides when
+    /// there are no bytes to go by.
+    pub fn detect(header: &[u8], path: &str) -> Self {
+        if header.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else if header.is_empty() {
+            Self::from_path(path)
+        } else {
+            Compression::None
+        }
+    }
+
+    fn from_path(path: &str) -> Self {
+        let extension = Path::new(path).extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("gz" | "gzip") => Compression::Gzip,
+            Some("zst" | "zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// The compression of a file, read from its first bytes
+    pub fn of_file(path: &str) -> Result<Self> {
+        let mut header = Vec::new();
+        File::open(path)?.take(ZSTD_MAGIC.len() as u64).read_to_end(&mut header)?;
+        Ok(Self::detect(&header, path))
+    }
+}
+
+/// How a file or stdin source is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Delimited text such as CSV, TSV or whitespace-separated columns
+    Delimited,
+    /// A JSON array of records
+    Json,
+    /// One JSON record per line (newline-delimited JSON, JSON Lines)
+    Ndjson,
+    /// Apache Parquet, read with the `columnar` feature
+    Parquet,
+    /// Arrow IPC file or stream (Feather v2), read with the `columnar` feature
+    Arrow,
+}
+
+impl InputFormat {
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "csv" | "tsv" | "text" => Ok(InputFormat::Delimited),
+            "json" => Ok(InputFormat::Json),
+            "ndjson" | "jsonl" => Ok(InputFormat::Ndjson),
+            "parquet" => Ok(InputFormat::Parquet),
+            "arrow" | "ipc" | "feather" => Ok(InputFormat::Arrow),
+            _ => Err(anyhow!("--format must be csv, json, ndjson, parquet or arrow, got: {}", text)),
+        }
+    }
+
+    /// The format implied by a file extension, looking past a compression
+    /// extension as in data.csv.gz; anything unrecognised, including stdin,
+    /// is read as delimited text
+    pub fn from_path(path: &str) -> Self {
+        let mut path = Path::new(path);
+        if Compression::from_path(&path.to_string_lossy()) != Compression::None {
+            path = Path::new(path.file_stem().unwrap_or_default());
+        }
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("json") => InputFormat::Json,
+            Some("ndjson" | "jsonl") => InputFormat::Ndjson,
+            Some("parquet" | "pq") => InputFormat::Parquet,
+            Some("arrow" | "arrows" | "ipc" | "feather") => InputFormat::Arrow,
+            _ => InputFormat::Delimited,
+        }
+    }
+}
+
+/// Whether a field holds a missing-value marker such as NA, NaN, null or nothing
+pub fn is_missing(field: &str) -> bool {
+    let field = field.trim();
+    MISSING_VALUES.iter().any(|marker| field.eq_ignore_ascii_case(marker))
+}
+
+/// Name of a source for messages, with stdin spelled out
+pub fn display_name(path: &str) -> &str {
+    if path == STDIN_SOURCE { "<stdin>" } else { path }
+}
+
+/// Settings for reading file and stdin sources
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Field separator; detected from the first lines when not given
+    pub delimiter: Option<Delimiter>,
+    /// The first row is data; columns are labelled by position
+    pub no_header: bool,
+    /// Lines to skip before the header, such as a preamble
+    pub skip_rows: usize,
+    /// Whether unreadable rows stop the plot, or are skipped with or without a warning
+    pub on_bad_row: BadRowPolicy,
+    /// Layout of the source; taken from the file extension when not given
+    pub format: Option<InputFormat>,
+    /// Column name or dotted field path for x; the first column, or the row
+    /// index for JSON, when not given
+    pub x: Option<String>,
+    /// Column name or dotted field path for y; the second column when not given
+    pub y: Option<String>,
+    /// Pattern with named groups `x` and `y` that pulls values out of each
+    /// line of a text source, such as a log, in place of a structured format
+    pub regex: Option<String>,
+    /// Aggregate large numeric inputs into this many M4 buckets as they are
+    /// read, for plots that only need each pixel column's extremes
+    pub downsample: Option<usize>,
+    /// Keep reading as the source grows, redrawing the plot for new rows
+    pub follow: bool,
+    /// While following, plot only the latest rows or those of a recent period
+    pub window: Option<Window>,
+    /// Lines of the source dropped ahead of this input, so that line numbers
+    /// in messages, and positions used as x, continue from them
+    pub row_offset: usize,
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_delimiter(mut self, delimiter: Option<Delimiter>) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_no_header(mut self, no_header: bool) -> Self {
+        self.no_header = no_header;
+        self
+    }
+
+    pub fn with_skip_rows(mut self, skip_rows: usize) -> Self {
+        self.skip_rows = skip_rows;
+        self
+    }
+
+    pub fn with_on_bad_row(mut self, on_bad_row: BadRowPolicy) -> Self {
+        self.on_bad_row = on_bad_row;
+        self
+    }
+
+    pub fn with_format(mut self, format: Option<InputFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_x(mut self, x: Option<String>) -> Self {
+        self.x = x;
+        self
+    }
+
+    pub fn with_y(mut self, y: Option<String>) -> Self {
+        self.y = y;
+        self
+    }
+
+    pub fn with_regex(mut self, regex: Option<String>) -> Self {
+        self.regex = regex;
+        self
+    }
+
+    pub fn with_downsample(mut self, downsample: Option<usize>) -> Self {
+        self.downsample = downsample;
+        self
+    }
+
+    pub fn with_follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    pub fn with_window(mut self, window: Option<Window>) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_row_offset(mut self, row_offset: usize) -> Self {
+        self.row_offset = row_offset;
+        self
+    }
+
+    /// The format to read `path` in: the one given, or the one its extension implies
+    pub fn format_for(&self, path: &str) -> InputFormat {
+        self.format.unwrap_or_else(|| InputFormat::from_path(path))
+    }
+}
+
+/// Open a data source for reading, treating "-" as standard input.
+/// Gzip and zstd data is decompressed as it is read.
+pub fn open_input(path: &str) -> Result<Box<dyn Read>> {
+    let input: Box<dyn Read> = if path == STDIN_SOURCE {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path)?)
+    };
+
+    let mut input = BufReader::new(input);
+    let header = input.fill_buf()?;
+    match Compression::detect(header, path) {
+        Compression::None => Ok(Box::new(input)),
+        Compression::Gzip => Ok(Box::new(MultiGzDecoder::new(input))),
+        Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(input)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_missing_file() {
+        assert!(open_input("does-not-exist.csv").is_err());
+    }
+
+    #[test]
+    fn test_missing_values_and_policy() {
+        for field in ["", " NA ", "nan", "NaN", "NULL"] {
+            assert!(is_missing(field), "{:?}", field);
+        }
+        assert!(!is_missing("0") && !is_missing("none"));
+
+        assert_eq!(BadRowPolicy::parse("warn").unwrap(), BadRowPolicy::Warn);
+        assert!(BadRowPolicy::parse("ignore").is_err());
+        assert!(BadRowPolicy::Error.handle(anyhow!("bad")).is_err());
+        assert!(BadRowPolicy::Skip.handle(anyhow!("bad")).is_ok());
+    }
+
+    #[test]
+    fn test_window_parse() {
+        assert_eq!(Window::parse("500").unwrap(), Window::Rows(500));
+        assert_eq!(Window::parse("5m").unwrap(), Window::Duration(Duration::from_secs(300)));
+        assert_eq!(Window::parse("1.5h").unwrap(), Window::Duration(Duration::from_secs(5400)));
+        for text in ["0", "5 minutes", "m", "-5s", "ten"] {
+            assert!(Window::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_format_from_option_or_extension() {
+        assert_eq!(InputFormat::from_path("metrics.JSON"), InputFormat::Json);
+        assert_eq!(InputFormat::from_path("events.jsonl"), InputFormat::Ndjson);
+        assert_eq!(InputFormat::from_path("-"), InputFormat::Delimited);
+        assert_eq!(InputFormat::from_path("part-0001.parquet"), InputFormat::Parquet);
+        assert_eq!(InputFormat::from_path("frame.feather"), InputFormat::Arrow);
+        let options = ReadOptions::new().with_format(Some(InputFormat::parse("ndjson").unwrap()));
+        assert_eq!(options.format_for("data.csv"), InputFormat::Ndjson);
+        assert!(InputFormat::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_compressed_input() {
+        let text = "x,y\n1,2\n3,4\n";
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut gzip, text.as_bytes()).unwrap();
+        let zstd = zstd::encode_all(text.as_bytes(), 0).unwrap();
+
+        // Named without a compression extension, so only the magic bytes tell
+        for (name, data) in [("gzipped.csv", gzip.finish().unwrap()), ("zstd.data", zstd)] {
+            let path = std::env::temp_dir().join(format!("fastplot-{}-{}", std::process::id(), name));
+            std::fs::write(&path, data).unwrap();
+            let path = path.to_string_lossy().into_owned();
+            assert_ne!(Compression::of_file(&path).unwrap(), Compression::None);
+
+            let mut contents = String::new();
+            open_input(&path).unwrap().read_to_string(&mut contents).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(contents, text);
+        }
+
+        assert_eq!(InputFormat::from_path("metrics.csv.gz"), InputFormat::Delimited);
+        assert_eq!(InputFormat::from_path("events.ndjson.zst"), InputFormat::Ndjson);
+        // Plain text under a compressed name is read as it is
+        assert_eq!(Compression::detect(b"x,y", "archive.csv.GZ"), Compression::None);
+        assert_eq!(Compression::detect(b"", "archive.csv.GZ"), Compression::Gzip);
+        let path = std::env::temp_dir().join(format!("fastplot-plain-{}.csv.gz", std::process::id()));
+        std::fs::write(&path, "x,y\n1,2\n").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let mut contents = String::new();
+        open_input(&path).unwrap().read_to_string(&mut contents).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents, "x,y\n1,2\n");
+    }
+
+    #[test]
+    fn test_open_existing_file() {
+        let mut contents = String::new();
+        open_input("test-data/sine.csv").unwrap().read_to_string(&mut contents).unwrap();
+        assert!(contents.starts_with("x,y"));
+    }
+}
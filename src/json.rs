@@ -0,0 +1,283 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
+use crate::data::{Dataset, PointCollector};
+use crate::input::{self, InputFormat, ReadOptions};
+use crate::timestamp;
+
+/// Plot two fields of JSON records, read from an array or from one record per
+/// line. Fields are named by `--x` and `--y` as dotted paths such as
+/// `metrics.latency_ms`, where numeric segments index arrays; without `--x`
+/// the record's position is used. x values may be numbers, timestamps or labels.
+pub fn parse_json(file_path: &str, options: &ReadOptions) -> Result<Dataset> {
+    read_json(input::open_input(file_path)?, file_path, options)
+}
+
+/// Read JSON records from `input`, naming it `file_path` in messages
+pub fn read_json(input: Box<dyn Read>, file_path: &str, options: &ReadOptions) -> Result<Dataset> {
+    let mut records = read_records(input, options.format_for(file_path), file_path, options)?.peekable();
+
+    let Some(y_path) = options.y.as_deref() else {
+        let fields = match records.peek() {
+            Some(Ok(record)) => field_paths(&record.value).join(", "),
+            _ => String::new(),
+        };
+        return Err(anyhow!(
+            "JSON input needs --y naming the field to plot; records have fields: {}",
+            fields
+        ));
+    };
+    let x_path = options.x.as_deref();
+
+    let mut collector = PointCollector::new().with_downsample(options.downsample);
+    let mut first = None;
+    let (mut found_x, mut found_y, mut is_time) = (false, false, false);
+
+    for (index, result) in records.enumerate() {
+        let record = result?;
+        let location = || format!("{}, {}", input::display_name(file_path), record.location);
+
+        let y_value = lookup(&record.value, y_path);
+        found_y |= y_value.is_some();
+        let y = match number(y_value) {
+            Ok(y) => y,
+            Err(value) => {
+                let error = anyhow!("Invalid number {} for '{}' in {}", value, y_path, location());
+                options.on_bad_row.handle(error)?;
+                None
+            }
+        };
+
+        let (x_text, x) = match x_path {
+            Some(path) => {
+                let x_value = lookup(&record.value, path);
+                found_x |= x_value.is_some();
+                match x_value {
+                    None | Some(Value::Null) => (String::new(), None),
+                    Some(Value::Number(n)) => (n.to_string(), n.as_f64()),
+                    Some(Value::String(text)) => match text.parse::<f64>() {
+                        Ok(x) => (text.clone(), Some(x)),
+                        Err(_) => {
+                            let x = timestamp::parse_timestamp(text);
+                            is_time |= x.is_some();
+                            (text.clone(), x)
+                        }
+                    },
+                    Some(Value::Bool(flag)) => (flag.to_string(), None),
+                    Some(value) => {
+                        let error = anyhow!("Field '{}' in {} is not a single value: {}", path, location(), value);
+                        options.on_bad_row.handle(error)?;
+                        continue;
+                    }
+                }
+            }
+            None => {
+                let index = index + options.row_offset;
+                (index.to_string(), Some(index as f64))
+            }
+        };
+
+        collector.push(&x_text, x, y);
+        first.get_or_insert(record.value);
+    }
+
+    // A path that matches no record at all is almost certainly a typo
+    if let Some(first) = &first {
+        for (path, found) in [(x_path, found_x), (Some(y_path), found_y)] {
+            if let (Some(path), false) = (path, found) {
+                return Err(anyhow!(
+                    "Field '{}' not found in {}; records have fields: {}",
+                    path,
+                    input::display_name(file_path),
+                    field_paths(first).join(", ")
+                ));
+            }
+        }
+    }
+
+    let is_time = is_time && !collector.is_categorical();
+    let x_label = x_path.unwrap_or("index").to_string();
+    Ok(collector.finish(x_label, y_path.to_string()).with_time(is_time))
+}
+
+/// A parsed record and where it came from, for messages
+struct Record {
+    location: String,
+    value: Value,
+}
+
+/// Records of a JSON array, parsed whole, or of newline-delimited JSON, parsed
+/// lazily line by line. A line that is not valid JSON is handed to the bad row
+/// policy; blank lines are skipped.
+fn read_records(
+    input: Box<dyn Read>,
+    format: InputFormat,
+    file_path: &str,
+    options: &ReadOptions,
+) -> Result<Box<dyn Iterator<Item = Result<Record>>>> {
+    let source = input::display_name(file_path).to_string();
+    if format != InputFormat::Ndjson {
+        let value: Value = serde_json::from_reader(BufReader::new(input))
+            .map_err(|e| anyhow!("Invalid JSON in {}: {}", source, e))?;
+        let Value::Array(items) = value else {
+            return Err(anyhow!("Expected a JSON array of records in {}", source));
+        };
+        return Ok(Box::new(items.into_iter().enumerate().map(|(index, value)| {
+            Ok(Record { location: format!("record {}", index + 1), value })
+        })));
+    }
+
+    let (policy, offset) = (options.on_bad_row, options.row_offset);
+    Ok(Box::new(BufReader::new(input).lines().enumerate().filter_map(move |(index, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e.into())),
+        };
+        if line.trim().is_empty() {
+            return None;
+        }
+        let location = format!("line {}", index + 1 + offset);
+        match serde_json::from_str(&line) {
+            Ok(value) => Some(Ok(Record { location, value })),
+            Err(e) => {
+                let error = anyhow!("Invalid JSON in {}, {}: {}", source, location, e);
+                policy.handle(error).err().map(Err)
+            }
+        }
+    })))
+}
+
+/// The value at a dotted path. Objects are searched for the longest matching
+/// key first, so keys that themselves contain dots still resolve.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    child(value, path).or_else(|| {
+        path.match_indices('.')
+            .rev()
+            .find_map(|(i, _)| lookup(child(value, &path[..i])?, &path[i + 1..]))
+    })
+}
+
+fn child<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(fields) => fields.get(key),
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+/// A y value: numbers and numeric strings, `None` for null, absent or
+/// missing-value strings, and the offending value otherwise
+fn number(value: Option<&Value>) -> std::result::Result<Option<f64>, &Value> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) => Ok(n.as_f64()),
+        Some(Value::String(text)) if input::is_missing(text) => Ok(None),
+        Some(value @ Value::String(text)) => text.trim().parse().map(Some).map_err(|_| value),
+        Some(value) => Err(value),
+    }
+}
+
+/// Dotted paths of the scalar fields of a record, for messages
+fn field_paths(value: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_paths(value, String::new(), &mut paths);
+    paths
+}
+
+fn collect_paths(value: &Value, prefix: String, paths: &mut Vec<String>) {
+    let join = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                collect_paths(field, join(key), paths);
+            }
+        }
+        Value::Array(items) if !prefix.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                collect_paths(item, join(&index.to_string()), paths);
+            }
+        }
+        _ if !prefix.is_empty() => paths.push(prefix),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataPoint;
+    use crate::input::BadRowPolicy;
+    use serde_json::json;
+
+    fn write(name: &str, text: &str) -> String {
+        let path = std::env::temp_dir().join(format!("fastplot-{}-{}", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_dotted_paths() {
+        let record = json!({"ts": 1, "metrics": {"latency_ms": 12.5, "hosts": [{"cpu": 0.5}]}, "a.b": 3});
+        assert_eq!(lookup(&record, "metrics.latency_ms"), Some(&json!(12.5)));
+        assert_eq!(lookup(&record, "metrics.hosts.0.cpu"), Some(&json!(0.5)));
+        assert_eq!(lookup(&record, "a.b"), Some(&json!(3)));
+        assert_eq!(lookup(&record, "metrics.missing"), None);
+        assert_eq!(field_paths(&record), vec!["a.b", "metrics.hosts.0.cpu", "metrics.latency_ms", "ts"]);
+    }
+
+    #[test]
+    fn test_ndjson_with_timestamps_and_gaps() {
+        let path = write("events.ndjson", concat!(
+            "{\"ts\": \"2024-03-01T10:00:00Z\", \"metrics\": {\"latency_ms\": 12}}\n",
+            "\n",
+            "{\"ts\": \"2024-03-01T10:01:00Z\", \"metrics\": {\"latency_ms\": null}}\n",
+            "{\"ts\": \"2024-03-01T10:02:00Z\", \"metrics\": {\"latency_ms\": \"15.5\"}}\n",
+        ));
+        let options = ReadOptions::new().with_x(Some("ts".into())).with_y(Some("metrics.latency_ms".into()));
+        let dataset = parse_json(&path, &options).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(dataset.is_time && !dataset.is_categorical);
+        assert_eq!(dataset.y_label, "metrics.latency_ms");
+        let ys: Vec<f64> = dataset.points.iter().map(DataPoint::y).collect();
+        assert_eq!(ys[0], 12.0);
+        assert!(ys[1].is_nan());
+        assert_eq!(ys[2], 15.5);
+        assert_eq!(dataset.points[1].x_numeric(), Some(1709287260.0));
+    }
+
+    #[test]
+    fn test_json_array_with_labels_and_index() {
+        let path = write("regions.json", r#"[{"region": "north", "sales": 3}, {"region": "south", "sales": 5}]"#);
+        let options = ReadOptions::new().with_x(Some("region".into())).with_y(Some("sales".into()));
+        let dataset = parse_json(&path, &options).unwrap();
+        assert!(dataset.is_categorical);
+        assert_eq!(dataset.categories, vec!["north", "south"]);
+
+        let dataset = parse_json(&path, &ReadOptions::new().with_y(Some("sales".into()))).unwrap();
+        assert_eq!(dataset.x_label, "index");
+        assert_eq!(dataset.points[1].x_numeric(), Some(1.0));
+
+        let error = parse_json(&path, &ReadOptions::new().with_y(Some("sale".into()))).unwrap_err();
+        assert!(error.to_string().contains("Field 'sale' not found"), "{}", error);
+        assert!(error.to_string().ends_with("records have fields: region, sales"), "{}", error);
+        assert!(parse_json(&path, &ReadOptions::new()).unwrap_err().to_string().contains("needs --y"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bad_ndjson_lines_follow_policy() {
+        let path = write("bad.jsonl", "{\"t\": 1, \"v\": 2}\n{\"t\": 2, \n{\"t\": 3, \"v\": \"high\"}\n{\"t\": 4, \"v\": 5}\n");
+        let options = ReadOptions::new().with_x(Some("t".into())).with_y(Some("v".into()));
+        let error = parse_json(&path, &options).unwrap_err().to_string();
+        assert!(error.contains("line 2"), "{}", error);
+
+        let dataset = parse_json(&path, &options.with_on_bad_row(BadRowPolicy::Skip)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let points: Vec<(Option<f64>, f64)> = dataset.points.iter().map(|p| (p.x_numeric(), p.y())).collect();
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0], (Some(1.0), 2.0));
+        assert!(points[1].1.is_nan());
+        assert_eq!(points[2], (Some(4.0), 5.0));
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use crate::color;
 
 /// Priority levels for rendering (higher overwrites lower)
@@ -121,10 +122,10 @@ impl Canvas {
             }
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
-        let mut output = String::new();
-        
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for row in 0..self.height {
             let mut line = String::new();
             for (col, ch) in self.buffer[row].iter().enumerate() {
@@ -138,11 +139,10 @@ impl Canvas {
                     line.push(*ch);
                 }
             }
-            output.push_str(line.trim_end());
-            output.push('\n');
+            writeln!(f, "{}", line.trim_end())?;
         }
         
-        output
+        Ok(())
     }
 }
 
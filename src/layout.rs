@@ -1,5 +1,10 @@
 use crate::coordinates::{DataBounds, Margins};
-use crate::ticks::{Tick, TickGenerator};
+use crate::color;
+use crate::ticks::{Tick, TickGenerator, TimeTickGenerator};
+
+/// Canvas size, in character cells, used by the axis-based plots
+pub const DEFAULT_CANVAS_WIDTH: usize = 80;
+pub const DEFAULT_CANVAS_HEIGHT: usize = 24;
 
 #[derive(Debug, Clone)]
 pub struct Layout {
@@ -22,6 +27,7 @@ pub struct LayoutEngine {
     canvas_height: usize,
     show_labels: bool,
     label_padding: usize,
+    time_x_axis: bool,
 }
 
 impl LayoutEngine {
@@ -31,6 +37,7 @@ impl LayoutEngine {
             canvas_height: height,
             show_labels: true,
             label_padding: 1,
+            time_x_axis: false,
         }
     }
 
@@ -40,10 +47,20 @@ impl LayoutEngine {
         self
     }
 
+    /// Treat x values as epoch seconds and place ticks on calendar boundaries
+    pub fn with_time_x_axis(mut self, time_x_axis: bool) -> Self {
+        self.time_x_axis = time_x_axis;
+        self
+    }
+
     pub fn calculate_layout(&self, data_bounds: &DataBounds) -> Layout {
         let tick_gen = TickGenerator::default();
         
-        let x_ticks = tick_gen.generate_ticks(data_bounds.min_x, data_bounds.max_x);
+        let x_ticks = if self.time_x_axis {
+            TimeTickGenerator::default().generate_ticks(data_bounds.min_x, data_bounds.max_x)
+        } else {
+            tick_gen.generate_ticks(data_bounds.min_x, data_bounds.max_x)
+        };
         let y_ticks = tick_gen.generate_ticks(data_bounds.min_y, data_bounds.max_y);
         
         let margins = self.calculate_margins(&x_ticks, &y_ticks);
@@ -106,7 +123,7 @@ impl LayoutEngine {
 
         ticks.iter().filter_map(|tick| {
             let norm = (tick.value - bounds.min_x) / x_range;
-            if norm >= 0.0 && norm <= 1.0 {
+            if (0.0..=1.0).contains(&norm) {
                 let col = area.left + (norm * area.width as f64).round() as usize;
                 if col <= area.left + area.width {
                     Some((col, tick.clone()))
@@ -131,7 +148,7 @@ impl LayoutEngine {
 
         ticks.iter().filter_map(|tick| {
             let norm = (tick.value - bounds.min_y) / y_range;
-            if norm >= 0.0 && norm <= 1.0 {
+            if (0.0..=1.0).contains(&norm) {
                 let row = area.top + ((1.0 - norm) * area.height as f64).round() as usize;
                 if row >= area.top && row <= area.top + area.height {
                     Some((row, tick.clone()))
@@ -157,6 +174,20 @@ pub fn format_x_axis_label(x_label: &str, layout: &Layout) -> String {
     format!("\n{}{}\n", padding, x_label)
 }
 
+/// Format a legend with one "marker label" entry per line, indented to line up
+/// with the plot area
+pub fn format_legend(entries: &[(char, &str, Option<&str>)], indent: usize) -> String {
+    let indent = " ".repeat(indent);
+    let mut output = String::new();
+    for (marker, label, color) in entries {
+        let marker = color
+            .and_then(|c| color::apply_color(*marker, c))
+            .unwrap_or_else(|| marker.to_string());
+        output.push_str(&format!("{}{} {}\n", indent, marker, label));
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,15 +1,21 @@
 use crate::coordinates::{NumericCoordinate, DataBounds, CoordinateTransformer};
-use crate::layout::LayoutEngine;
+use crate::layout::{LayoutEngine, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT};
 use crate::layered_canvas::{LayeredCanvas, RenderPriority};
 use crate::line_style::LineStyle;
 use crate::line_drawing::LineRenderer;
 use crate::data::{Dataset, DataPoint};
+use crate::analysis::Feature;
+use crate::color;
 
 pub struct LinePlot {
     width: usize,
     height: usize,
     data: Vec<DataPoint>,
+    series: Vec<(String, Vec<DataPoint>)>,
+    preserve_order: bool,
+    features: Vec<Feature>,
     style: LineStyle,
+    is_time: bool,
     title: String,
     x_label: String,
     y_label: String,
@@ -18,12 +24,20 @@ pub struct LinePlot {
 impl LinePlot {
     pub fn new(dataset: &Dataset, title: &str, width: usize, height: usize) -> Self {
         let data = dataset.points.clone();
+        let series = dataset.series_slices()
+            .into_iter()
+            .map(|(label, points)| (label.to_string(), points.to_vec()))
+            .collect();
         
         Self {
             width,
             height,
             data,
+            series,
+            preserve_order: dataset.preserve_order,
+            features: dataset.features.clone(),
             style: LineStyle::default(),
+            is_time: dataset.is_time,
             title: title.to_string(),
             x_label: dataset.x_label.clone(),
             y_label: dataset.y_label.clone(),
@@ -41,23 +55,9 @@ impl LinePlot {
             return format!("{}\n\nNo data to plot\n", self.title);
         }
 
-        // Sort data by x coordinate for proper line connections
-        let mut sorted_data = self.data.clone();
-        sorted_data.sort_by(|a, b| {
-            let a_x = match a {
-                DataPoint::Numeric(x, _) => *x,
-                DataPoint::Categorical(_, _) => 0.0, // Categorical not supported in line plots
-            };
-            let b_x = match b {
-                DataPoint::Numeric(x, _) => *x,
-                DataPoint::Categorical(_, _) => 0.0,
-            };
-            a_x.partial_cmp(&b_x).unwrap()
-        });
-
         // Calculate bounds and layout
-        let bounds = self.calculate_bounds_with_padding(&sorted_data);
-        let layout_engine = LayoutEngine::new(self.width, self.height);
+        let bounds = self.calculate_bounds_with_padding(&self.data);
+        let layout_engine = LayoutEngine::new(self.width, self.height).with_time_x_axis(self.is_time);
         let layout = layout_engine.calculate_layout(&bounds);
         
         // Create transformer
@@ -65,7 +65,7 @@ impl LinePlot {
             bounds,
             self.width,
             self.height,
-            layout.margins.clone(),
+            layout.margins,
         );
         
         // Create layered canvas
@@ -75,21 +75,52 @@ impl LinePlot {
         self.draw_axes(&mut canvas, &layout);
         self.draw_ticks(&mut canvas, &layout);
         
-        // Convert data points to screen coordinates
-        let screen_points: Vec<_> = sorted_data
-            .iter()
-            .filter_map(|p| transformer.transform_data_point(p))
-            .collect();
-        
-        // Draw connecting lines
-        if self.style.show_lines && screen_points.len() > 1 {
-            self.draw_lines(&mut canvas, &screen_points, color);
+        // Draw each series in its own colour
+        let mut legend = Vec::new();
+        for (index, (label, points)) in self.series.iter().enumerate() {
+            let series_color = color::series_color(index, self.series.len(), color);
+            
+            // Sort data by x coordinate for proper line connections, unless the
+            // points are already in drawing order (e.g. parametric curves)
+            let mut sorted_data = points.clone();
+            if !self.preserve_order {
+                sorted_data.sort_by(|a, b| {
+                    let a_x = match a {
+                        DataPoint::Numeric(x, _) => *x,
+                        DataPoint::Categorical(_, _) => 0.0, // Categorical not supported in line plots
+                    };
+                    let b_x = match b {
+                        DataPoint::Numeric(x, _) => *x,
+                        DataPoint::Categorical(_, _) => 0.0,
+                    };
+                    a_x.partial_cmp(&b_x).unwrap()
+                });
+            }
+            
+            // Gaps split the series into segments that are drawn separately
+            for segment in sorted_data.split(DataPoint::is_gap) {
+                // Convert data points to screen coordinates
+                let screen_points: Vec<_> = segment
+                    .iter()
+                    .filter_map(|p| transformer.transform_data_point(p))
+                    .collect();
+                
+                // Draw connecting lines
+                if self.style.show_lines && screen_points.len() > 1 {
+                    self.draw_lines(&mut canvas, &screen_points, series_color);
+                }
+                
+                // Draw data points (on top of lines)
+                if self.style.show_points {
+                    self.draw_points(&mut canvas, &screen_points, series_color);
+                }
+            }
+            
+            let marker = if self.style.show_points { self.style.point_char } else { self.style.line_char };
+            legend.push((marker, label.as_str(), series_color));
         }
         
-        // Draw data points (on top of lines)
-        if self.style.show_points {
-            self.draw_points(&mut canvas, &screen_points, color);
-        }
+        self.draw_features(&mut canvas, &transformer, &layout);
         
         // Flatten layers and format output
         let final_canvas = canvas.flatten();
@@ -100,6 +131,9 @@ impl LinePlot {
         output.push('\n');
         output.push_str(&self.render_with_y_labels(&final_canvas, &layout));
         output.push_str(&crate::layout::format_x_axis_label(&self.x_label, &layout));
+        if legend.len() > 1 {
+            output.push_str(&crate::layout::format_legend(&legend, layout.plot_area.left));
+        }
         
         output
     }
@@ -117,7 +151,7 @@ impl LinePlot {
             }
             
             // Get line points
-            let line_points = LineRenderer::bresenham_line(start.clone(), end.clone());
+            let line_points = LineRenderer::bresenham_line(*start, *end);
             
             // Draw line, but skip the actual data points if we're showing them
             for point in line_points {
@@ -138,6 +172,28 @@ impl LinePlot {
         }
     }
 
+    /// Mark roots, extrema and intersections with their glyph and tag, the tag
+    /// placed right of the glyph unless that would run past the plot area
+    fn draw_features(&self, canvas: &mut LayeredCanvas, transformer: &CoordinateTransformer, layout: &crate::layout::Layout) {
+        let label_layer = canvas.get_layer(RenderPriority::Labels);
+        let right_edge = layout.plot_area.left + layout.plot_area.width;
+        
+        for feature in &self.features {
+            let Some(point) = transformer.data_to_screen(NumericCoordinate::new(feature.x, feature.y)) else {
+                continue;
+            };
+            label_layer.draw_point(point.col, point.row, feature.kind.glyph());
+            
+            let tag_len = feature.tag.chars().count();
+            let tag_col = if point.col + 1 + tag_len <= right_edge {
+                point.col + 1
+            } else {
+                point.col.saturating_sub(tag_len)
+            };
+            label_layer.draw_text(tag_col, point.row, &feature.tag);
+        }
+    }
+
     fn calculate_bounds_with_padding(&self, data: &[DataPoint]) -> DataBounds {
         // Convert to numeric coordinates for bounds calculation
         let numeric_coords: Vec<NumericCoordinate> = data
@@ -261,6 +317,6 @@ impl LinePlot {
 
 
 pub fn render_line_plot(dataset: &Dataset, title: &str, style: LineStyle, color: Option<&str>) -> String {
-    let plot = LinePlot::new(dataset, title, 80, 24).with_style(style);
+    let plot = LinePlot::new(dataset, title, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT).with_style(style);
     plot.render(color)
 }
\ No newline at end of file
@@ -8,13 +8,36 @@ mod line_drawing;
 mod layered_canvas;
 mod line_plot;
 mod function;
+mod expression;
+mod calculus;
+mod analysis;
+mod implicit;
+mod ode;
+mod math_functions;
 mod bar_chart;
 mod color;
 mod plot_config;
+mod input;
+mod delimited;
+mod downsample;
+mod follow;
+mod json;
+mod pattern;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "columnar")]
+mod columnar;
+mod sparkline;
+mod pie_chart;
+mod timestamp;
+mod candlestick;
+mod polar_plot;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use anyhow::Result;
 use plot_config::{PlotConfig, PlotType, PlotCommand};
+use delimited::Delimiter;
+use input::{BadRowPolicy, InputFormat, ReadOptions, Window};
 
 #[derive(Parser)]
 #[command(name = "fastplot")]
@@ -28,8 +51,9 @@ struct Cli {
 enum Commands {
     /// Create scatter plots from data files or functions
     Scatter {
-        /// Data source: CSV file path or function expression (e.g., "data.csv" or "function:x^2")
-        source: String,
+        /// Data sources: CSV file paths, "-" for stdin, or function expressions (e.g., "data.csv", "function:x^2" or "polar:cos(2*theta)"); give several to overlay series
+        #[arg(required = true)]
+        sources: Vec<String>,
         /// Plot title
         #[arg(short, long, default_value = "Scatter Plot")]
         title: String,
@@ -45,11 +69,29 @@ enum Commands {
         /// Number of points to evaluate for functions
         #[arg(long, default_value = "200")]
         points: usize,
+        /// Refine function samples where the curve bends, using --points as the evaluation budget
+        #[arg(long)]
+        adaptive: bool,
+        /// Expression parameter as name=value, or a sweep name=start..end:count drawing one curve per value; repeatable
+        #[arg(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+        /// Overlay the numerical derivative of each function as an extra series
+        #[arg(long)]
+        derivative: bool,
+        /// Overlay the cumulative integral of each function from the start of the range
+        #[arg(long)]
+        integral: bool,
+        /// Plot CSV data as angle (degrees) and radius on polar axes
+        #[arg(long)]
+        polar: bool,
+        #[command(flatten)]
+        input: InputArgs,
     },
     /// Create line plots with various styling options
     Line {
-        /// Data source: CSV file path or function expression (e.g., "data.csv" or "function:sin(x)")
-        source: String,
+        /// Data sources: CSV file paths, "-" for stdin, or function expressions (e.g., "data.csv", "function:sin(x)" or "polar:1+cos(theta)"); give several to overlay series
+        #[arg(required = true)]
+        sources: Vec<String>,
         /// Plot title
         #[arg(short, long, default_value = "Line Plot")]
         title: String,
@@ -77,6 +119,29 @@ enum Commands {
         /// Number of points to evaluate for functions
         #[arg(long, default_value = "200")]
         points: usize,
+        /// Refine function samples where the curve bends, using --points as the evaluation budget
+        #[arg(long)]
+        adaptive: bool,
+        /// Expression parameter as name=value, or a sweep name=start..end:count drawing one curve per value; repeatable
+        #[arg(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+        /// Overlay the numerical derivative of each function as an extra series
+        #[arg(long)]
+        derivative: bool,
+        /// Overlay the cumulative integral of each function from the start of the range
+        #[arg(long)]
+        integral: bool,
+        /// Mark roots, minima, maxima and intersections of functions with labelled glyphs
+        #[arg(long)]
+        features: bool,
+        /// List the marked points in a table below the chart (implies --features)
+        #[arg(long)]
+        feature_table: bool,
+        /// Plot CSV data as angle (degrees) and radius on polar axes
+        #[arg(long)]
+        polar: bool,
+        #[command(flatten)]
+        input: InputArgs,
     },
     /// Create bar charts for categorical or numeric data
     Bar {
@@ -100,10 +165,141 @@ enum Commands {
         /// Number of points to evaluate for functions
         #[arg(long, default_value = "200")]
         points: usize,
+        /// Refine function samples where the curve bends, using --points as the evaluation budget
+        #[arg(long)]
+        adaptive: bool,
+        /// Expression parameter as name=value, or a sweep name=start..end:count drawing one curve per value; repeatable
+        #[arg(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
         /// Custom category order as comma-separated list (e.g., "Q1,Q2,Q3,Q4")
         #[arg(long)]
         category_order: Option<String>,
+        #[command(flatten)]
+        input: InputArgs,
     },
+    /// Create compact single-line sparklines without axes or title
+    Spark {
+        /// Data source: CSV file path, "-" for stdin, or function expression (e.g., "data.csv" or "function:sin(x)")
+        source: String,
+        /// Width in characters (defaults to one per data point, up to 80)
+        #[arg(short = 'w', long)]
+        width: Option<usize>,
+        /// Annotate with the min, max and last values
+        #[arg(short = 'a', long)]
+        annotate: bool,
+        /// Color for the plot (named color or hex code)
+        #[arg(short, long)]
+        color: Option<String>,
+        /// X range for functions as min:max (e.g., "-5:5")
+        #[arg(short, long)]
+        range: Option<String>,
+        /// Number of points to evaluate for functions
+        #[arg(long, default_value = "200")]
+        points: usize,
+        /// Refine function samples where the curve bends, using --points as the evaluation budget
+        #[arg(long)]
+        adaptive: bool,
+        /// Expression parameter as name=value, or a sweep name=start..end:count drawing one curve per value; repeatable
+        #[arg(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Create pie or donut charts showing each category's share of the total
+    Pie {
+        /// Data source: CSV file path, "-" for stdin, or function expression (e.g., "data.csv")
+        source: String,
+        /// Plot title
+        #[arg(short, long, default_value = "Pie Chart")]
+        title: String,
+        /// Character used to fill wedges
+        #[arg(short = 'f', long, default_value = "█")]
+        fill_char: char,
+        /// Draw a donut with a hollow centre
+        #[arg(long)]
+        donut: bool,
+        /// X range for functions as min:max (e.g., "-5:5")
+        #[arg(short, long)]
+        range: Option<String>,
+        /// Number of points to evaluate for functions
+        #[arg(long, default_value = "200")]
+        points: usize,
+        /// Custom category order as comma-separated list (e.g., "Q1,Q2,Q3,Q4")
+        #[arg(long)]
+        category_order: Option<String>,
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Create candlestick (OHLC) charts from date, open, high, low and close columns
+    Candle {
+        /// Data source: CSV file path or "-" for stdin, with date, open, high, low, close and optional volume columns
+        source: String,
+        /// Plot title
+        #[arg(short, long, default_value = "Candlestick Chart")]
+        title: String,
+        /// Show a volume sub-panel below the price chart
+        #[arg(long)]
+        volume: bool,
+        /// Color for candles that closed up (named color or hex code)
+        #[arg(long, default_value = "green")]
+        up_color: String,
+        /// Color for candles that closed down (named color or hex code)
+        #[arg(long, default_value = "red")]
+        down_color: String,
+        #[command(flatten)]
+        input: InputArgs,
+    },
+}
+
+/// Options for reading CSV, JSON and other data files
+#[derive(Args)]
+struct InputArgs {
+    /// Field delimiter: a character, or tab, space (runs of whitespace), comma, semicolon or pipe; detected when omitted
+    #[arg(long)]
+    delimiter: Option<String>,
+    /// The first row is data rather than column names; columns are labelled by position
+    #[arg(long)]
+    no_header: bool,
+    /// Number of lines to skip at the start of the file, before the header
+    #[arg(long, default_value = "0")]
+    skip_rows: usize,
+    /// What to do with rows that cannot be read: stop with an error, skip them, or skip them with a warning
+    #[arg(long, default_value = "error", value_name = "error|skip|warn")]
+    on_bad_row: String,
+    /// Input format; taken from the file extension (.json, .ndjson, .jsonl, .parquet, .arrow) when omitted
+    #[arg(long, value_name = "csv|json|ndjson|parquet|arrow")]
+    format: Option<String>,
+    /// Column name, or dotted field path for JSON (e.g., "ts"), to use for x
+    #[arg(long, value_name = "FIELD")]
+    x: Option<String>,
+    /// Column name, or dotted field path for JSON (e.g., "metrics.latency_ms"), to use for y
+    #[arg(long, value_name = "FIELD")]
+    y: Option<String>,
+    /// Read each line with a pattern whose named groups (?P<x>...) and (?P<y>...) capture the values; other lines are skipped
+    #[arg(long, value_name = "PATTERN")]
+    regex: Option<String>,
+    /// Keep reading as the file or stdin grows, redrawing the plot in place for new rows
+    #[arg(long)]
+    follow: bool,
+    /// With --follow, plot only the latest rows (e.g., "500") or those of a recent period (e.g., "5m", "30s", "2h")
+    #[arg(long, requires = "follow", value_name = "ROWS|DURATION", default_value_t = follow::DEFAULT_WINDOW_ROWS.to_string())]
+    window: String,
+}
+
+impl InputArgs {
+    fn read_options(self) -> Result<ReadOptions> {
+        Ok(ReadOptions::new()
+            .with_delimiter(self.delimiter.as_deref().map(Delimiter::parse).transpose()?)
+            .with_no_header(self.no_header)
+            .with_skip_rows(self.skip_rows)
+            .with_on_bad_row(BadRowPolicy::parse(&self.on_bad_row)?)
+            .with_format(self.format.as_deref().map(InputFormat::parse).transpose()?)
+            .with_x(self.x)
+            .with_y(self.y)
+            .with_regex(self.regex)
+            .with_follow(self.follow)
+            .with_window(Some(Window::parse(&self.window)?)))
+    }
 }
 
 fn main() -> Result<()> {
@@ -111,12 +307,19 @@ fn main() -> Result<()> {
     
     // Convert CLI arguments to unified plot command - this provides the deep module interface
     let plot_command = match cli.command {
-        Commands::Scatter { source, title, point_char, color, range, points } => {
-            let config = PlotConfig::new(source)
+        Commands::Scatter { sources, title, point_char, color, range, points, polar, adaptive, params, derivative, integral, input } => {
+            let config = PlotConfig::new(sources[0].clone())
+                .with_extra_sources(sources[1..].to_vec())
                 .with_title(title)
                 .with_color(color)
                 .with_range(range)
-                .with_points(points);
+                .with_points(points)
+                .with_polar(polar)
+                .with_adaptive(adaptive)
+                .with_params(params)
+                .with_derivative(derivative)
+                .with_integral(integral)
+                .with_input(input.read_options()?);
             
             let plot_type = PlotType::scatter()
                 .with_point_char(point_char);
@@ -124,7 +327,7 @@ fn main() -> Result<()> {
             PlotCommand::new(config, plot_type)
         }
         Commands::Line { 
-            source, 
+            sources, 
             title, 
             style, 
             points_only, 
@@ -133,13 +336,30 @@ fn main() -> Result<()> {
             line_char, 
             color,
             range,
-            points
+            points,
+            polar,
+            adaptive,
+            params,
+            derivative,
+            integral,
+            features,
+            feature_table,
+            input
         } => {
-            let config = PlotConfig::new(source)
+            let config = PlotConfig::new(sources[0].clone())
+                .with_extra_sources(sources[1..].to_vec())
                 .with_title(title)
                 .with_color(color)
                 .with_range(range)
-                .with_points(points);
+                .with_points(points)
+                .with_polar(polar)
+                .with_adaptive(adaptive)
+                .with_params(params)
+                .with_derivative(derivative)
+                .with_integral(integral)
+                .with_features(features)
+                .with_feature_table(feature_table)
+                .with_input(input.read_options()?);
             
             // Create line style based on arguments
             let line_style = match style.as_str() {
@@ -158,12 +378,15 @@ fn main() -> Result<()> {
             
             PlotCommand::new(config, plot_type)
         }
-        Commands::Bar { source, title, bar_char, bar_width, color, range, points, category_order } => {
+        Commands::Bar { source, title, bar_char, bar_width, color, range, points, adaptive, params, category_order, input } => {
             let config = PlotConfig::new(source)
                 .with_title(title)
                 .with_color(color)
                 .with_range(range)
-                .with_points(points);
+                .with_points(points)
+                .with_adaptive(adaptive)
+                .with_params(params)
+                .with_input(input.read_options()?);
             
             let category_order_vec = category_order.map(|order| {
                 order.split(',').map(|s| s.trim().to_string()).collect()
@@ -176,8 +399,57 @@ fn main() -> Result<()> {
             
             PlotCommand::new(config, plot_type)
         }
+        Commands::Spark { source, width, annotate, color, range, points, adaptive, params, input } => {
+            let config = PlotConfig::new(source)
+                .with_color(color)
+                .with_range(range)
+                .with_points(points)
+                .with_adaptive(adaptive)
+                .with_params(params)
+                .with_input(input.read_options()?);
+            
+            let plot_type = PlotType::spark()
+                .with_spark_width(width)
+                .with_summary(annotate);
+            
+            PlotCommand::new(config, plot_type)
+        }
+        Commands::Pie { source, title, fill_char, donut, range, points, category_order, input } => {
+            let config = PlotConfig::new(source)
+                .with_title(title)
+                .with_range(range)
+                .with_points(points)
+                .with_input(input.read_options()?);
+            
+            let category_order_vec = category_order.map(|order| {
+                order.split(',').map(|s| s.trim().to_string()).collect()
+            });
+            
+            let plot_type = PlotType::pie()
+                .with_fill_char(fill_char)
+                .with_donut(donut)
+                .with_category_order(category_order_vec);
+            
+            PlotCommand::new(config, plot_type)
+        }
+        Commands::Candle { source, title, volume, up_color, down_color, input } => {
+            let config = PlotConfig::new(source)
+                .with_title(title)
+                .with_input(input.read_options()?);
+            
+            let plot_type = PlotType::candle()
+                .with_volume(volume)
+                .with_candle_colors(up_color, down_color);
+            
+            PlotCommand::new(config, plot_type)
+        }
     };
     
+    // A followed source is redrawn as it grows instead of printed once
+    if plot_command.follows() {
+        return plot_command.follow();
+    }
+
     // Execute the unified command - single point of execution
     let output = plot_command.execute()?;
     print!("{}", output);
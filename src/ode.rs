@@ -0,0 +1,342 @@
+use anyhow::{Result, anyhow};
+use crate::data::{DataPoint, Dataset};
+use crate::function::Function;
+
+/// Span integrated when no range is given, starting at the initial condition
+const DEFAULT_SPAN: f64 = 10.0;
+
+/// Error tolerances of the adaptive solver, relative to the state and absolute
+const RELATIVE_TOLERANCE: f64 = 1e-6;
+const ABSOLUTE_TOLERANCE: f64 = 1e-9;
+
+/// Dormand–Prince 5(4) tableau: nodes, stage weights, and the 5th and 4th order solutions
+const DP_C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const DP_A: [[f64; 6]; 7] = [
+    [0.0; 6],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+    [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+    [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+const DP_B5: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+const DP_B4: [f64; 7] = [
+    5179.0 / 57600.0, 0.0, 7571.0 / 16695.0, 393.0 / 640.0, -92097.0 / 339200.0, 187.0 / 2100.0, 1.0 / 40.0,
+];
+
+/// A state along the solution: x and the value of each state variable
+type State = (f64, Vec<f64>);
+
+/// An initial value problem: first-order equations `y' = f(x, y, ...)` for each
+/// state variable, with every variable's value given at a common starting x
+#[derive(Clone)]
+pub struct OdeSystem {
+    names: Vec<String>,
+    derivatives: Vec<Function>,
+    x0: f64,
+    initial: Vec<f64>,
+}
+
+impl OdeSystem {
+    /// Parse `;`-separated equations (`y' = ...` or `dy/dx = ...`) and initial
+    /// conditions (`y(0) = 1`), e.g. "y' = z; z' = -y; y(0) = 1; z(0) = 0"
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut equations: Vec<(String, String)> = Vec::new();
+        let mut conditions: Vec<(String, f64, f64)> = Vec::new();
+
+        for part in text.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (lhs, rhs) = part.split_once('=')
+                .ok_or_else(|| anyhow!("Expected an equation or initial condition, got: {}", part))?;
+            let (lhs, rhs) = (lhs.trim(), rhs.trim());
+
+            if let Some(name) = lhs.strip_suffix('\'')
+                .or_else(|| lhs.strip_prefix('d').and_then(|l| l.strip_suffix("/dx")))
+            {
+                let name = valid_name(name.trim(), part)?;
+                if equations.iter().any(|(existing, _)| existing == name) {
+                    return Err(anyhow!("Variable '{}' has more than one equation", name));
+                }
+                equations.push((name.to_string(), rhs.to_string()));
+            } else if let Some((name, x0)) = lhs.strip_suffix(')').and_then(|l| l.split_once('(')) {
+                let name = valid_name(name.trim(), part)?;
+                conditions.push((name.to_string(), constant(x0)?, constant(rhs)?));
+            } else {
+                return Err(anyhow!("Expected y' = ... or y(x0) = value, got: {}", part));
+            }
+        }
+
+        if equations.is_empty() {
+            return Err(anyhow!("ODE source needs at least one equation such as y' = -y"));
+        }
+
+        let x0 = conditions.first()
+            .map(|(_, x0, _)| *x0)
+            .ok_or_else(|| anyhow!("ODE source needs initial conditions such as y(0) = 1"))?;
+        let mut initial = Vec::new();
+        for (name, _) in &equations {
+            let matching: Vec<&(String, f64, f64)> = conditions.iter().filter(|(n, _, _)| n == name).collect();
+            match matching[..] {
+                [(_, x, value)] if *x == x0 => initial.push(*value),
+                [_] => return Err(anyhow!("All initial conditions must be given at the same x ({})", x0)),
+                [] => return Err(anyhow!("Missing initial condition for '{}', e.g. {}({}) = 1", name, name, x0)),
+                _ => return Err(anyhow!("Variable '{}' has more than one initial condition", name)),
+            }
+        }
+        if let Some((name, _, _)) = conditions.iter().find(|(n, _, _)| !equations.iter().any(|(e, _)| e == n)) {
+            return Err(anyhow!("Initial condition for '{}', which has no equation", name));
+        }
+
+        Ok(Self {
+            names: equations.iter().map(|(name, _)| name.clone()).collect(),
+            derivatives: equations.iter().map(|(_, rhs)| Function::new(rhs)).collect(),
+            x0,
+            initial,
+        })
+    }
+
+    /// Builder for binding user-defined parameters in every equation
+    pub fn with_params(self, params: &[(String, f64)]) -> Self {
+        Self {
+            derivatives: self.derivatives.into_iter().map(|f| f.with_params(params)).collect(),
+            ..self
+        }
+    }
+
+    /// Range covered when none is given: from the initial condition onwards
+    pub fn default_range(&self) -> (f64, f64) {
+        (self.x0, self.x0 + DEFAULT_SPAN)
+    }
+
+    /// Solve over a range, integrating outwards from the initial condition in
+    /// both directions as needed. RK4 takes `points` even steps across the
+    /// range; adaptive RK45 chooses its own steps and fails after `points`.
+    /// Returns one dataset per state variable, in equation order.
+    pub fn generate_datasets(&self, x_min: f64, x_max: f64, points: usize, adaptive: bool) -> Result<Vec<Dataset>> {
+        if points < 2 {
+            return Err(anyhow!("Number of points must be at least 2"));
+        }
+        let mut variables = vec!["x"];
+        variables.extend(self.names.iter().map(String::as_str));
+        for derivative in &self.derivatives {
+            derivative.validate(&variables)?;
+        }
+        let step = (x_max - x_min) / (points - 1) as f64;
+        let solve = |to: f64| {
+            if adaptive {
+                self.solve_rk45(to, step, points)
+            } else {
+                self.solve_rk4(to, step)
+            }
+        };
+
+        let mut states = Vec::new();
+        if x_min < self.x0 {
+            let mut backward = solve(x_min)?;
+            backward.reverse();
+            backward.pop();
+            states.extend(backward);
+        }
+        states.extend(solve(x_max.max(self.x0))?);
+        states.retain(|(x, _)| (x_min..=x_max).contains(x));
+
+        Ok(self.names.iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let points = states.iter().map(|(x, values)| DataPoint::Numeric(*x, values[i])).collect();
+                Dataset::new_numeric(points, "x".to_string(), format!("{}(x)", name)).with_series_label(name)
+            })
+            .collect())
+    }
+
+    /// Names of the state variables, in equation order
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Derivatives of every state variable at a point
+    fn slopes(&self, x: f64, values: &[f64]) -> Result<Vec<f64>> {
+        let mut variables: Vec<(&str, f64)> = vec![("x", x)];
+        variables.extend(self.names.iter().map(String::as_str).zip(values.iter().copied()));
+        self.derivatives.iter()
+            .map(|f| f.evaluate_with(&variables).map_err(|e| anyhow!("Failed to evaluate expression: {}", e)))
+            .collect()
+    }
+
+    /// Classic fourth-order Runge–Kutta from the initial condition to `to`,
+    /// with steps as close to `step` as divide the distance evenly
+    fn solve_rk4(&self, to: f64, step: f64) -> Result<Vec<State>> {
+        let distance = to - self.x0;
+        let steps = (distance.abs() / step.abs()).ceil() as usize;
+        let mut states = vec![(self.x0, self.initial.clone())];
+        if steps == 0 {
+            return Ok(states);
+        }
+        let h = distance / steps as f64;
+
+        let mut y = self.initial.clone();
+        for i in 0..steps {
+            let x = self.x0 + i as f64 * h;
+            let k1 = self.slopes(x, &y)?;
+            let k2 = self.slopes(x + h / 2.0, &offset(&y, &[(h / 2.0, &k1)]))?;
+            let k3 = self.slopes(x + h / 2.0, &offset(&y, &[(h / 2.0, &k2)]))?;
+            let k4 = self.slopes(x + h, &offset(&y, &[(h, &k3)]))?;
+            y = offset(&y, &[(h / 6.0, &k1), (h / 3.0, &k2), (h / 3.0, &k3), (h / 6.0, &k4)]);
+
+            // A solution that blows up ends the curve rather than the plot
+            if y.iter().any(|v| !v.is_finite()) {
+                break;
+            }
+            states.push((self.x0 + (i + 1) as f64 * h, y.clone()));
+        }
+        Ok(states)
+    }
+
+    /// Dormand–Prince RK45 with step size control, from the initial condition
+    /// to `to`, starting from `initial_step` and taking at most `max_steps` steps
+    fn solve_rk45(&self, to: f64, initial_step: f64, max_steps: usize) -> Result<Vec<State>> {
+        let direction = (to - self.x0).signum();
+        let mut h = initial_step.abs() * direction;
+        let mut x = self.x0;
+        let mut y = self.initial.clone();
+        let mut states = vec![(x, y.clone())];
+
+        while (to - x) * direction > 0.0 {
+            if states.len() > max_steps {
+                return Err(anyhow!(
+                    "ODE solver needed more than {} steps to reach x = {}; raise --points",
+                    max_steps, to
+                ));
+            }
+            if (x + h - to) * direction > 0.0 {
+                h = to - x;
+            }
+
+            let mut k: Vec<Vec<f64>> = Vec::with_capacity(7);
+            for stage in 0..7 {
+                let terms: Vec<(f64, &[f64])> = (0..stage)
+                    .map(|j| (h * DP_A[stage][j], k[j].as_slice()))
+                    .collect();
+                k.push(self.slopes(x + DP_C[stage] * h, &offset(&y, &terms))?);
+            }
+            let fifth = offset(&y, &weighted(&k, &DP_B5, h));
+            let fourth = offset(&y, &weighted(&k, &DP_B4, h));
+
+            // Scaled RMS error of the step; accept at or below 1
+            let error = (fifth.iter()
+                .zip(&fourth)
+                .zip(&y)
+                .map(|((a, b), old)| {
+                    let scale = ABSOLUTE_TOLERANCE + RELATIVE_TOLERANCE * a.abs().max(old.abs());
+                    ((a - b) / scale).powi(2)
+                })
+                .sum::<f64>() / y.len() as f64)
+                .sqrt();
+
+            if !error.is_finite() || fifth.iter().any(|v| !v.is_finite()) {
+                break;
+            }
+            if error <= 1.0 {
+                x += h;
+                y = fifth;
+                states.push((x, y.clone()));
+            }
+
+            // Standard controller: safety factor 0.9, growth limited to 0.2..5
+            let factor = if error == 0.0 { 5.0 } else { (0.9 * error.powf(-0.2)).clamp(0.2, 5.0) };
+            h *= factor;
+            if h.abs() < f64::EPSILON * x.abs().max(1.0) {
+                break;
+            }
+        }
+        Ok(states)
+    }
+}
+
+/// y + Σ hᵢ·kᵢ
+fn offset(y: &[f64], terms: &[(f64, &[f64])]) -> Vec<f64> {
+    y.iter()
+        .enumerate()
+        .map(|(i, value)| value + terms.iter().map(|(h, k)| h * k[i]).sum::<f64>())
+        .collect()
+}
+
+fn weighted<'a>(k: &'a [Vec<f64>], weights: &[f64], h: f64) -> Vec<(f64, &'a [f64])> {
+    k.iter().zip(weights).map(|(k, w)| (h * w, k.as_slice())).collect()
+}
+
+fn valid_name<'a>(name: &'a str, part: &str) -> Result<&'a str> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid || name == "x" {
+        return Err(anyhow!("Invalid state variable '{}' in: {}", name, part));
+    }
+    Ok(name)
+}
+
+/// Evaluate a constant expression such as "0", "pi/2" or "-1.5"
+fn constant(text: &str) -> Result<f64> {
+    Function::new(text.trim())
+        .evaluate_with(&[])
+        .map_err(|_| anyhow!("Expected a number, got: {}", text.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_value(dataset: &Dataset) -> (f64, f64) {
+        let last = dataset.points.last().unwrap();
+        (last.x_numeric().unwrap(), last.y())
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        let system = OdeSystem::parse("y' = -0.5*y; y(0) = 1").unwrap();
+        for adaptive in [false, true] {
+            let datasets = system.generate_datasets(0.0, 4.0, 41, adaptive).unwrap();
+            let (x, y) = last_value(&datasets[0]);
+            assert_eq!(x, 4.0);
+            assert!((y - (-2.0_f64).exp()).abs() < 1e-6, "adaptive {}: {}", adaptive, y);
+        }
+    }
+
+    #[test]
+    fn test_system_and_backward_integration() {
+        // y'' = -y as a system: y = cos(x), z = -sin(x)
+        let system = OdeSystem::parse("y' = z; dz/dx = -y; y(0) = 1; z(0) = 0").unwrap();
+        let datasets = system.generate_datasets(-3.0, 3.0, 121, false).unwrap();
+
+        assert_eq!(system.names(), ["y", "z"]);
+        assert_eq!(datasets[1].y_label, "z(x)");
+        for (i, dataset) in datasets.iter().enumerate() {
+            assert_eq!(dataset.points.first().unwrap().x_numeric(), Some(-3.0));
+            for point in &dataset.points {
+                let x = point.x_numeric().unwrap();
+                let expected = if i == 0 { x.cos() } else { -x.sin() };
+                assert!((point.y() - expected).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_forced_equation_from_request() {
+        let system = OdeSystem::parse("y' = -0.5*y + sin(x); y(0)=1").unwrap();
+        let rk4 = system.generate_datasets(0.0, 10.0, 201, false).unwrap();
+        let rk45 = system.generate_datasets(0.0, 10.0, 201, true).unwrap();
+        let (_, a) = last_value(&rk4[0]);
+        let (_, b) = last_value(&rk45[0]);
+        assert!((a - b).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |text: &str| OdeSystem::parse(text).err().unwrap().to_string();
+        assert!(message("y' = -y").contains("initial conditions"));
+        assert!(message("y' = z; z' = -y; y(0) = 1").contains("'z'"));
+        assert!(OdeSystem::parse("y' = -y; y(0) = 1; y(1) = 2").is_err());
+        assert!(OdeSystem::parse("y' = -y; y(0) = 1; w(0) = 2").is_err());
+        assert!(OdeSystem::parse("x' = 1; x(0) = 0").is_err());
+        assert!(OdeSystem::parse("y' = -y; y(0) = 1").unwrap().default_range() == (0.0, 10.0));
+    }
+}
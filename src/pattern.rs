@@ -0,0 +1,131 @@
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::io::{BufRead, BufReader};
+use crate::data::{Dataset, PointCollector};
+use crate::input::{self, ReadOptions};
+use crate::timestamp;
+
+/// Pull points out of free-form text such as a log. Each line is matched
+/// against `pattern`, whose named group `y` captures the value and optional
+/// group `x` its position: a number, a timestamp or time of day, or a label.
+/// Without an `x` group the line number is used. Lines that do not match are
+/// skipped, and their count is reported once the input is read.
+pub fn parse_pattern(file_path: &str, pattern: &str, options: &ReadOptions) -> Result<Dataset> {
+    let regex = compile(pattern)?;
+    let input = BufReader::new(input::open_input(file_path)?);
+    let (dataset, skipped) = extract(input, &regex, file_path, options)?;
+    if skipped > 0 {
+        eprintln!(
+            "Skipped {} line{} of {} that did not match --regex",
+            skipped,
+            if skipped == 1 { "" } else { "s" },
+            input::display_name(file_path)
+        );
+    }
+    Ok(dataset)
+}
+
+pub fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| anyhow!("Invalid --regex: {}", e))
+}
+
+/// The dataset read from `input`, and the number of lines that did not match
+pub fn extract(mut input: impl BufRead, regex: &Regex, file_path: &str, options: &ReadOptions) -> Result<(Dataset, usize)> {
+    let groups: Vec<&str> = regex.capture_names().flatten().collect();
+    if !groups.contains(&"y") {
+        return Err(anyhow!("--regex needs a named group (?P<y>...) capturing the value to plot"));
+    }
+    let has_x = groups.contains(&"x");
+
+    let mut collector = PointCollector::new().with_downsample(options.downsample);
+    let mut is_time = false;
+    let mut skipped = 0;
+    let mut bytes = Vec::new();
+    let mut number = 0;
+
+    loop {
+        bytes.clear();
+        if input.read_until(b'\n', &mut bytes)? == 0 {
+            break;
+        }
+        number += 1;
+        if number <= options.skip_rows {
+            continue;
+        }
+        let number = number + options.row_offset;
+        // Logs are not always valid UTF-8; a stray byte should not end the plot
+        let line = String::from_utf8_lossy(&bytes);
+        let Some(captures) = regex.captures(line.trim_end_matches(['\n', '\r'])) else {
+            skipped += 1;
+            continue;
+        };
+
+        let y_text = captures.name("y").map_or("", |m| m.as_str());
+        let y = if input::is_missing(y_text) {
+            None
+        } else if let Ok(y) = y_text.trim().parse() {
+            Some(y)
+        } else {
+            let error = anyhow!("Invalid number '{}' in {}, line {}", y_text, input::display_name(file_path), number);
+            options.on_bad_row.handle(error)?;
+            None
+        };
+
+        if !has_x {
+            collector.push(&number.to_string(), Some(number as f64), y);
+            continue;
+        }
+        let x_text = captures.name("x").map_or("", |m| m.as_str());
+        let x = x_text.trim().parse().ok().or_else(|| {
+            let x = timestamp::parse_timestamp(x_text);
+            is_time |= x.is_some();
+            x
+        });
+        collector.push(x_text, x, y);
+    }
+
+    let is_time = is_time && !collector.is_categorical();
+    let x_label = if has_x { "x" } else { "line" };
+    Ok((collector.finish(x_label.to_string(), "y".to_string()).with_time(is_time), skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataPoint;
+    use std::io::Cursor;
+
+    fn read(text: &str, pattern: &str) -> Result<(Dataset, usize)> {
+        extract(Cursor::new(text.to_string()), &Regex::new(pattern).unwrap(), "app.log", &ReadOptions::new())
+    }
+
+    #[test]
+    fn test_times_of_day_and_skipped_lines() {
+        let log = "\
+            INFO request took 123ms at 12:01:02\n\
+            DEBUG cache warm\n\
+            INFO request took 98ms at 12:01:05\n\
+            INFO request took -ms at 12:01:09\n\
+            INFO request took 140ms at 12:01:11\n";
+        let (dataset, skipped) = read(log, r"took (?P<y>\d+)ms at (?P<x>[\d:]+)").unwrap();
+        assert_eq!(skipped, 2);
+        assert!(dataset.is_time);
+        let points: Vec<(Option<f64>, f64)> = dataset.points.iter().map(|p| (p.x_numeric(), p.y())).collect();
+        assert_eq!(points, vec![(Some(43262.0), 123.0), (Some(43265.0), 98.0), (Some(43271.0), 140.0)]);
+    }
+
+    #[test]
+    fn test_line_numbers_labels_and_bad_values() {
+        let (dataset, _) = read("loss=0.9\nepoch done\nloss=0.5\n", r"loss=(?P<y>\S+)").unwrap();
+        assert_eq!(dataset.x_label, "line");
+        assert_eq!(dataset.points.iter().map(|p| p.x_numeric().unwrap()).collect::<Vec<_>>(), vec![1.0, 3.0]);
+
+        let (dataset, _) = read("GET /a 12\nPOST /b 30\n", r"^(?P<x>\w+) \S+ (?P<y>\d+)").unwrap();
+        assert!(dataset.is_categorical && !dataset.is_time);
+        assert!(matches!(&dataset.points[1], DataPoint::Categorical(method, y) if method == "POST" && *y == 30.0));
+
+        let error = read("a 1\nb x\n", r"(?P<x>\w) (?P<y>\w+)").unwrap_err().to_string();
+        assert_eq!(error, "Invalid number 'x' in app.log, line 2");
+        assert!(read("1", r"(?P<value>\d)").unwrap_err().to_string().contains("(?P<y>...)"));
+    }
+}
@@ -0,0 +1,212 @@
+use std::f64::consts::PI;
+use crate::coordinates::CELL_ASPECT;
+use crate::layered_canvas::Canvas;
+use crate::data::{Dataset, DataPoint};
+use crate::color;
+
+/// Inner radius of the donut variant as a fraction of the outer radius
+const DONUT_HOLE: f64 = 0.5;
+
+/// Gap between the pie and its legend
+const LEGEND_GAP: usize = 3;
+
+pub struct PieChart {
+    width: usize,
+    height: usize,
+    slices: Vec<(String, f64)>,
+    title: String,
+    fill_char: char,
+    donut: bool,
+}
+
+impl PieChart {
+    pub fn new(dataset: &Dataset, title: &str, width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            slices: Self::collect_slices(dataset),
+            title: title.to_string(),
+            fill_char: '█',
+            donut: false,
+        }
+    }
+
+    pub fn with_fill_char(mut self, fill_char: char) -> Self {
+        self.fill_char = fill_char;
+        self
+    }
+
+    pub fn with_donut(mut self, donut: bool) -> Self {
+        self.donut = donut;
+        self
+    }
+
+    /// Sum values per label, keeping category order and dropping non-positive totals
+    fn collect_slices(dataset: &Dataset) -> Vec<(String, f64)> {
+        let mut slices: Vec<(String, f64)> = Vec::new();
+
+        if dataset.is_categorical {
+            for category in &dataset.categories {
+                slices.push((category.clone(), 0.0));
+            }
+        }
+
+        for point in dataset.points.iter().filter(|p| !p.is_gap()) {
+            let label = match point {
+                DataPoint::Categorical(category, _) => category.clone(),
+                DataPoint::Numeric(x, _) => x.to_string(),
+            };
+            match slices.iter_mut().find(|(name, _)| *name == label) {
+                Some((_, total)) => *total += point.y(),
+                None => slices.push((label, point.y())),
+            }
+        }
+
+        slices.retain(|(_, value)| *value > 0.0);
+        slices
+    }
+
+    pub fn render(&self) -> String {
+        let total: f64 = self.slices.iter().map(|(_, value)| value).sum();
+        if self.slices.is_empty() || total <= 0.0 {
+            return format!("{}\n\nNo positive values to plot\n", self.title);
+        }
+
+        let legend_lines = self.legend_lines(total);
+        let legend_width = legend_lines.iter()
+            .map(|line| line.chars().count() + 2)
+            .max()
+            .unwrap_or(0);
+
+        // Fit the pie into the space left of the legend, correcting for cell aspect
+        let pie_area_width = self.width.saturating_sub(legend_width + LEGEND_GAP).max(3);
+        let radius_rows = ((self.height as f64 - 1.0) / 2.0)
+            .min((pie_area_width as f64 - 1.0) / 2.0 / CELL_ASPECT)
+            .max(1.0);
+        let pie_cols = (radius_rows * CELL_ASPECT * 2.0).ceil() as usize + 1;
+        let pie_rows = (radius_rows * 2.0).ceil() as usize + 1;
+        let center_col = pie_cols as f64 / 2.0;
+        let center_row = pie_rows as f64 / 2.0;
+
+        let mut canvas = Canvas::new(self.width, pie_rows.max(legend_lines.len()));
+        self.draw_wedges(&mut canvas, total, center_col, center_row, radius_rows);
+
+        // Legend is vertically centred next to the pie
+        let legend_col = pie_cols + LEGEND_GAP;
+        let legend_top = pie_rows.saturating_sub(legend_lines.len()) / 2;
+        for (i, line) in legend_lines.iter().enumerate() {
+            let row = legend_top + i;
+            canvas.draw_point_with_color(legend_col, row, self.fill_char, Some(color::palette_color(i)));
+            canvas.draw_text(legend_col + 2, row, line);
+        }
+
+        let mut output = String::new();
+        output.push_str(&self.title);
+        output.push_str("\n\n");
+        output.push_str(&canvas.to_string());
+        output
+    }
+
+    fn draw_wedges(&self, canvas: &mut Canvas, total: f64, center_col: f64, center_row: f64, radius: f64) {
+        // Cumulative end angle of each wedge, clockwise from twelve o'clock
+        let mut boundaries = Vec::with_capacity(self.slices.len());
+        let mut cumulative = 0.0;
+        for (_, value) in &self.slices {
+            cumulative += value / total;
+            boundaries.push(cumulative * 2.0 * PI);
+        }
+
+        let inner_radius = if self.donut { radius * DONUT_HOLE } else { 0.0 };
+
+        for row in 0..canvas.height {
+            for col in 0..canvas.width {
+                let dx = (col as f64 + 0.5 - center_col) / CELL_ASPECT;
+                let dy = row as f64 + 0.5 - center_row;
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance >= radius || distance < inner_radius {
+                    continue;
+                }
+
+                let mut angle = dx.atan2(-dy);
+                if angle < 0.0 {
+                    angle += 2.0 * PI;
+                }
+
+                let wedge = boundaries.iter()
+                    .position(|end| angle < *end)
+                    .unwrap_or(self.slices.len() - 1);
+                canvas.draw_point_with_color(col, row, self.fill_char, Some(color::palette_color(wedge)));
+            }
+        }
+    }
+
+    fn legend_lines(&self, total: f64) -> Vec<String> {
+        let label_width = self.slices.iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        self.slices.iter()
+            .map(|(label, value)| {
+                format!("{:<width$}  {:>5.1}%", label, value / total * 100.0, width = label_width)
+            })
+            .collect()
+    }
+}
+
+pub fn render_pie_chart(dataset: &Dataset, title: &str, fill_char: char, donut: bool) -> String {
+    let chart = PieChart::new(dataset, title, 80, 21)
+        .with_fill_char(fill_char)
+        .with_donut(donut);
+    chart.render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions() -> Dataset {
+        let points = vec![
+            DataPoint::Categorical("North".to_string(), 30.0),
+            DataPoint::Categorical("South".to_string(), 10.0),
+            DataPoint::Categorical("North".to_string(), 10.0),
+            DataPoint::Categorical("East".to_string(), 0.0),
+        ];
+        let categories = vec!["North".to_string(), "South".to_string(), "East".to_string()];
+        Dataset::new_categorical(points, "Region".to_string(), "Sales".to_string(), categories)
+    }
+
+    #[test]
+    fn test_slices_are_aggregated() {
+        let chart = PieChart::new(&regions(), "Sales", 80, 21);
+        assert_eq!(chart.slices, vec![("North".to_string(), 40.0), ("South".to_string(), 10.0)]);
+    }
+
+    #[test]
+    fn test_legend_percentages() {
+        let output = render_pie_chart(&regions(), "Sales", '█', false);
+        assert!(output.starts_with("Sales\n\n"));
+        assert!(output.contains("North   80.0%"));
+        assert!(output.contains("South   20.0%"));
+    }
+
+    #[test]
+    fn test_donut_has_hole() {
+        let chart = PieChart::new(&regions(), "Sales", 80, 21).with_donut(true);
+        let mut canvas = Canvas::new(40, 21);
+        chart.draw_wedges(&mut canvas, 50.0, 20.0, 10.5, 10.0);
+
+        // Centre is empty, the ring is filled, and the first wedge starts at twelve o'clock
+        assert_eq!(canvas.buffer[10][20], ' ');
+        assert_eq!(canvas.buffer[10][5], '█');
+        assert_eq!(canvas.colors[2][21], Some(color::palette_color(0).to_string()));
+        assert_eq!(canvas.colors[2][18], Some(color::palette_color(1).to_string()));
+    }
+
+    #[test]
+    fn test_no_positive_values() {
+        let points = vec![DataPoint::Categorical("A".to_string(), -1.0)];
+        let dataset = Dataset::new_categorical(points, "x".to_string(), "y".to_string(), vec!["A".to_string()]);
+        assert!(render_pie_chart(&dataset, "Empty", '█', false).contains("No positive values"));
+    }
+}
@@ -1,5 +1,9 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use crate::function::AdaptiveSampling;
+use crate::input::ReadOptions;
+use crate::layout::{DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT};
 use crate::line_style::LineStyle;
+use crate::sparkline;
 
 /// Unified configuration for all plot types - implements "deep module" design
 /// by hiding complexity behind a simple interface
@@ -7,6 +11,8 @@ use crate::line_style::LineStyle;
 pub struct PlotConfig {
     /// Data source: CSV file path or function expression
     pub source: String,
+    /// Further sources overlaid on the same axes as separate series
+    pub extra_sources: Vec<String>,
     /// Plot title to display
     pub title: String,
     /// Optional color (named color or hex code)
@@ -15,6 +21,22 @@ pub struct PlotConfig {
     pub range: Option<String>,
     /// Number of points to evaluate for functions
     pub points: usize,
+    /// Treat x as an angle in degrees and y as a radius
+    pub polar: bool,
+    /// Refine function samples where the curve bends, using `points` as the budget
+    pub adaptive: bool,
+    /// Expression parameters and sweeps as "name=value" or "name=start..end:count"
+    pub params: Vec<String>,
+    /// Overlay the numerical derivative of each function
+    pub derivative: bool,
+    /// Overlay the cumulative integral of each function
+    pub integral: bool,
+    /// Mark roots, extrema and intersections of functions
+    pub features: bool,
+    /// List the marked features in a table below the chart
+    pub feature_table: bool,
+    /// How file and stdin sources are read (delimiter, header, skipped rows)
+    pub input: ReadOptions,
 }
 
 impl PlotConfig {
@@ -22,13 +44,28 @@ impl PlotConfig {
     pub fn new(source: String) -> Self {
         Self {
             source,
+            extra_sources: Vec::new(),
             title: "Plot".to_string(),
             color: None,
             range: None,
             points: 200,
+            polar: false,
+            adaptive: false,
+            params: Vec::new(),
+            derivative: false,
+            integral: false,
+            features: false,
+            feature_table: false,
+            input: ReadOptions::default(),
         }
     }
 
+    /// Builder pattern for overlaying additional sources
+    pub fn with_extra_sources(mut self, extra_sources: Vec<String>) -> Self {
+        self.extra_sources = extra_sources;
+        self
+    }
+
     /// Builder pattern for setting title
     pub fn with_title(mut self, title: String) -> Self {
         self.title = title;
@@ -52,6 +89,54 @@ impl PlotConfig {
         self.points = points;
         self
     }
+
+    /// Builder pattern for polar angle/radius data
+    pub fn with_polar(mut self, polar: bool) -> Self {
+        self.polar = polar;
+        self
+    }
+
+    /// Builder pattern for adaptive function sampling
+    pub fn with_adaptive(mut self, adaptive: bool) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// Builder pattern for expression parameters
+    pub fn with_params(mut self, params: Vec<String>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Builder pattern for derivative overlays
+    pub fn with_derivative(mut self, derivative: bool) -> Self {
+        self.derivative = derivative;
+        self
+    }
+
+    /// Builder pattern for integral overlays
+    pub fn with_integral(mut self, integral: bool) -> Self {
+        self.integral = integral;
+        self
+    }
+
+    /// Builder pattern for root, extremum and intersection markers
+    pub fn with_features(mut self, features: bool) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Builder pattern for printing the feature table
+    pub fn with_feature_table(mut self, feature_table: bool) -> Self {
+        self.feature_table = feature_table;
+        self
+    }
+
+    /// Builder pattern for file reading options
+    pub fn with_input(mut self, input: ReadOptions) -> Self {
+        self.input = input;
+        self
+    }
 }
 
 /// Plot-specific parameters separated by type
@@ -72,6 +157,20 @@ pub enum PlotType {
         bar_width: usize,
         category_order: Option<Vec<String>>,
     },
+    Spark {
+        width: Option<usize>,
+        show_summary: bool,
+    },
+    Pie {
+        fill_char: char,
+        donut: bool,
+        category_order: Option<Vec<String>>,
+    },
+    Candle {
+        show_volume: bool,
+        up_color: String,
+        down_color: String,
+    },
 }
 
 impl PlotType {
@@ -102,6 +201,32 @@ impl PlotType {
         }
     }
 
+    /// Create a sparkline plot type with default settings
+    pub fn spark() -> Self {
+        Self::Spark {
+            width: None,
+            show_summary: false,
+        }
+    }
+
+    /// Create a pie chart type with default settings
+    pub fn pie() -> Self {
+        Self::Pie {
+            fill_char: '█',
+            donut: false,
+            category_order: None,
+        }
+    }
+
+    /// Create a candlestick chart type with default settings
+    pub fn candle() -> Self {
+        Self::Candle {
+            show_volume: false,
+            up_color: "green".to_string(),
+            down_color: "red".to_string(),
+        }
+    }
+
     /// Builder method for scatter plot point character
     pub fn with_point_char(self, point_char: char) -> Self {
         match self {
@@ -180,12 +305,92 @@ impl PlotType {
         }
     }
 
-    /// Builder method for bar plot category order
+    /// Builder method for bar and pie chart category order
     pub fn with_category_order(self, category_order: Option<Vec<String>>) -> Self {
         match self {
             Self::Bar { bar_char, bar_width, .. } => {
                 Self::Bar { bar_char, bar_width, category_order }
             }
+            Self::Pie { fill_char, donut, .. } => {
+                Self::Pie { fill_char, donut, category_order }
+            }
+            _ => self,
+        }
+    }
+
+    /// Builder method for pie chart wedge character
+    pub fn with_fill_char(self, fill_char: char) -> Self {
+        match self {
+            Self::Pie { donut, category_order, .. } => {
+                Self::Pie { fill_char, donut, category_order }
+            }
+            _ => self,
+        }
+    }
+
+    /// Builder method for the donut variant of pie charts
+    pub fn with_donut(self, donut: bool) -> Self {
+        match self {
+            Self::Pie { fill_char, category_order, .. } => {
+                Self::Pie { fill_char, donut, category_order }
+            }
+            _ => self,
+        }
+    }
+
+    /// Builder method for the candlestick volume sub-panel
+    pub fn with_volume(self, show_volume: bool) -> Self {
+        match self {
+            Self::Candle { up_color, down_color, .. } => {
+                Self::Candle { show_volume, up_color, down_color }
+            }
+            _ => self,
+        }
+    }
+
+    /// Builder method for candlestick up/down colors
+    pub fn with_candle_colors(self, up_color: String, down_color: String) -> Self {
+        match self {
+            Self::Candle { show_volume, .. } => {
+                Self::Candle { show_volume, up_color, down_color }
+            }
+            _ => self,
+        }
+    }
+
+    /// Plot size in character cells, which adaptive function sampling refines towards
+    pub fn resolution(&self) -> AdaptiveSampling {
+        match self {
+            Self::Spark { width, .. } => AdaptiveSampling::new(
+                width.unwrap_or(sparkline::DEFAULT_MAX_WIDTH),
+                sparkline::SPARK_CHARS.len(),
+            ),
+            _ => AdaptiveSampling::new(DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT),
+        }
+    }
+
+    /// M4 buckets that file data may be aggregated into while it is read.
+    /// Two per column of a line plot keep every visible extreme; scatter
+    /// plots and sparklines show every point, so they read the data whole.
+    pub fn downsample_buckets(&self) -> Option<usize> {
+        match self {
+            Self::Line { .. } => Some(2 * DEFAULT_CANVAS_WIDTH),
+            _ => None,
+        }
+    }
+
+    /// Builder method for sparkline width
+    pub fn with_spark_width(self, width: Option<usize>) -> Self {
+        match self {
+            Self::Spark { show_summary, .. } => Self::Spark { width, show_summary },
+            _ => self,
+        }
+    }
+
+    /// Builder method for sparkline min/max/last annotation
+    pub fn with_summary(self, show_summary: bool) -> Self {
+        match self {
+            Self::Spark { width, .. } => Self::Spark { width, show_summary },
             _ => self,
         }
     }
@@ -206,20 +411,128 @@ impl PlotCommand {
     }
 
 
+    fn parse_source(&self, source: &str) -> Result<crate::data::Dataset> {
+        let options = crate::data::SourceOptions::new()
+            .with_range(self.config.range.clone())
+            .with_points(Some(self.config.points))
+            .with_adaptive(self.config.adaptive.then(|| self.plot_type.resolution()))
+            .with_params(self.config.params.clone())
+            .with_derivative(self.config.derivative)
+            .with_integral(self.config.integral)
+            .with_features(self.config.features || self.config.feature_table)
+            .with_input(self.read_options());
+        crate::data::parse_data_source(source, &options)
+    }
+
+    /// How files are read for this plot
+    fn read_options(&self) -> ReadOptions {
+        self.config.input.clone().with_downsample(self.downsample_buckets())
+    }
+
+    /// Polar plots keep the file's angle order, which bucketing by x would lose
+    fn downsample_buckets(&self) -> Option<usize> {
+        self.plot_type.downsample_buckets().filter(|_| !self.config.polar)
+    }
+
+    /// Whether the source is watched as it grows rather than read once
+    pub fn follows(&self) -> bool {
+        self.config.input.follow
+    }
+
+    /// Keep reading the source as it grows, redrawing the plot in place
+    /// until the input ends or the user interrupts
+    pub fn follow(&self) -> Result<()> {
+        if let PlotType::Candle { .. } = self.plot_type {
+            return Err(anyhow!("--follow is not available for candlestick charts"));
+        }
+        if !self.config.extra_sources.is_empty() {
+            return Err(anyhow!(
+                "--follow watches one source, but {} were given",
+                self.config.extra_sources.len() + 1
+            ));
+        }
+        let mut output = std::io::stdout();
+        crate::follow::follow(&self.config.source, &self.read_options(), &mut output, |dataset| self.render(dataset))
+    }
+
     /// Execute the plot command - single point of execution logic
     /// This method encapsulates all the complexity of different plot types
     pub fn execute(&self) -> Result<String> {
-        use crate::{data, scatter, line_plot, bar_chart};
+        use crate::{analysis, data, candlestick};
+
+        // Candlestick charts read several columns rather than a single x/y series
+        if let PlotType::Candle { show_volume, up_color, down_color } = &self.plot_type {
+            let input = &self.config.input;
+            let ignored = [("--x", input.x.is_some()), ("--y", input.y.is_some()), ("--regex", input.regex.is_some())];
+            if let Some((flag, _)) = ignored.iter().find(|(_, given)| *given) {
+                return Err(anyhow!(
+                    "{} is not available for candlestick charts, which find the date and OHLC columns by name",
+                    flag
+                ));
+            }
+            let ohlc = data::parse_ohlc(&self.config.source, &self.config.input)?;
+            return Ok(candlestick::render_candlestick_chart(
+                &ohlc,
+                &self.config.title,
+                *show_volume,
+                up_color,
+                down_color,
+            ));
+        }
 
         // Parse data source using unified configuration
-        let mut dataset = data::parse_data_source(
-            &self.config.source,
-            self.config.range.as_deref(),
-            Some(self.config.points),
-        )?;
+        let dataset = if self.config.extra_sources.is_empty() {
+            self.parse_source(&self.config.source)?
+        } else {
+            // Overlay every source as its own series, labelling unnamed ones by source
+            let mut datasets = Vec::new();
+            let mut analyzed = Vec::new();
+            for source in std::iter::once(&self.config.source).chain(&self.config.extra_sources) {
+                let mut parsed = self.parse_source(source)?;
+                if parsed.series.is_empty() {
+                    parsed = parsed.with_series_label(source);
+                }
+                analyzed.extend(parsed.analyzed.take());
+                datasets.push(parsed);
+            }
+            let mut combined = data::Dataset::combine(datasets, "y")?;
+
+            // Each source found its own features; crossings between sources are found here
+            let crossings = analysis::intersect_sources(&analyzed, self.config.points);
+            if !crossings.is_empty() {
+                let features = std::mem::take(&mut combined.features).into_iter().chain(crossings).collect();
+                combined = combined.with_features(analysis::tag_features(features));
+            }
+            combined
+        };
+        self.render(dataset)
+    }
+
+    /// Draw a dataset read from the sources as this command's plot
+    fn render(&self, mut dataset: crate::data::Dataset) -> Result<String> {
+        use crate::{analysis, data, scatter, line_plot, polar_plot, bar_chart, pie_chart};
+
+        // CSV angle/radius columns; polar: functions are already in radians
+        if self.config.polar {
+            dataset = dataset.into_polar_from_degrees()?;
+        }
 
         // Execute based on plot type, but with consistent interface
         match &self.plot_type {
+            PlotType::Scatter { point_char } if dataset.is_polar => {
+                let style = LineStyle {
+                    point_char: *point_char,
+                    show_lines: false,
+                    ..LineStyle::default()
+                };
+                let output = polar_plot::render_polar_plot(
+                    &dataset,
+                    &self.config.title,
+                    style,
+                    self.config.color.as_deref(),
+                );
+                Ok(output)
+            }
             PlotType::Scatter { point_char } => {
                 let output = scatter::render_scatter_plot(
                     &dataset,
@@ -250,12 +563,24 @@ impl PlotCommand {
                     line_style.line_char = *lc;
                 }
 
-                let output = line_plot::render_line_plot(
-                    &dataset,
-                    &self.config.title,
-                    line_style,
-                    self.config.color.as_deref(),
-                );
+                let mut output = if dataset.is_polar {
+                    polar_plot::render_polar_plot(
+                        &dataset,
+                        &self.config.title,
+                        line_style,
+                        self.config.color.as_deref(),
+                    )
+                } else {
+                    line_plot::render_line_plot(
+                        &dataset,
+                        &self.config.title,
+                        line_style,
+                        self.config.color.as_deref(),
+                    )
+                };
+                if self.config.feature_table {
+                    output.push_str(&analysis::format_table(&dataset.features));
+                }
                 Ok(output)
             }
             PlotType::Bar {
@@ -279,6 +604,35 @@ impl PlotCommand {
                 );
                 Ok(output)
             }
+            PlotType::Spark { width, show_summary } => {
+                let output = sparkline::render_sparkline(
+                    &dataset,
+                    *width,
+                    *show_summary,
+                    self.config.color.as_deref(),
+                );
+                Ok(output)
+            }
+            PlotType::Pie {
+                fill_char,
+                donut,
+                category_order,
+            } => {
+                if let Some(order) = category_order {
+                    if dataset.is_categorical {
+                        dataset = data::reorder_categories(dataset, order.clone())?;
+                    }
+                }
+
+                let output = pie_chart::render_pie_chart(
+                    &dataset,
+                    &self.config.title,
+                    *fill_char,
+                    *donut,
+                );
+                Ok(output)
+            }
+            PlotType::Candle { .. } => unreachable!("candlestick charts are rendered before parsing"),
         }
     }
 }
@@ -322,6 +676,37 @@ mod tests {
             }
             _ => panic!("Expected bar plot type"),
         }
+
+        let spark = PlotType::spark().with_spark_width(Some(20)).with_summary(true);
+        match spark {
+            PlotType::Spark { width, show_summary } => {
+                assert_eq!(width, Some(20));
+                assert!(show_summary);
+            }
+            _ => panic!("Expected spark plot type"),
+        }
+
+        let pie = PlotType::pie().with_donut(true).with_category_order(Some(vec!["B".to_string()]));
+        match pie {
+            PlotType::Pie { fill_char, donut, category_order } => {
+                assert_eq!(fill_char, '█');
+                assert!(donut);
+                assert_eq!(category_order, Some(vec!["B".to_string()]));
+            }
+            _ => panic!("Expected pie plot type"),
+        }
+
+        let candle = PlotType::candle()
+            .with_volume(true)
+            .with_candle_colors("blue".to_string(), "yellow".to_string());
+        match candle {
+            PlotType::Candle { show_volume, up_color, down_color } => {
+                assert!(show_volume);
+                assert_eq!(up_color, "blue");
+                assert_eq!(down_color, "yellow");
+            }
+            _ => panic!("Expected candle plot type"),
+        }
     }
 
     #[test]
@@ -334,4 +719,49 @@ mod tests {
         assert_eq!(command.config.source, "test.csv");
         assert_eq!(command.config.title, "Test");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_extra_sources_become_series() {
+        let config = PlotConfig::new("function:x".to_string())
+            .with_extra_sources(vec!["function:x^2".to_string()])
+            .with_range(Some("0:1".to_string()))
+            .with_points(5);
+        let output = PlotCommand::new(config, PlotType::line().with_lines_only(true))
+            .execute()
+            .unwrap();
+
+        assert!(output.contains(" x\n"));
+        assert!(output.contains(" x^2\n"));
+    }
+
+    #[test]
+    fn test_intersections_across_sources() {
+        let config = PlotConfig::new("function:x^2".to_string())
+            .with_extra_sources(vec!["function:x+2".to_string()])
+            .with_range(Some("-3:3".to_string()))
+            .with_feature_table(true);
+        let output = PlotCommand::new(config, PlotType::line()).execute().unwrap();
+
+        let crossings: Vec<Vec<&str>> = output.lines()
+            .filter(|line| line.contains("intersection"))
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(crossings, vec![
+            vec!["✕", "X1", "intersection", "x^2", "∩", "x+2", "-1.0000", "1.0000"],
+            vec!["✕", "X2", "intersection", "x^2", "∩", "x+2", "2.0000", "4.0000"],
+        ]);
+    }
+
+    #[test]
+    fn test_candle_rejects_series_flags() {
+        let config = PlotConfig::new("test-data/ohlc.csv".to_string())
+            .with_input(ReadOptions::new().with_y(Some("Volume".to_string())));
+        let error = PlotCommand::new(config, PlotType::candle()).execute().unwrap_err();
+        assert!(error.to_string().starts_with("--y is not available for candlestick charts"));
+
+        let config = PlotConfig::new("test-data/ohlc.csv".to_string())
+            .with_input(ReadOptions::new().with_regex(Some(r"x=(?P<x>\d+)".to_string())));
+        let error = PlotCommand::new(config, PlotType::candle()).execute().unwrap_err();
+        assert!(error.to_string().starts_with("--regex is not available"));
+    }
+}
@@ -0,0 +1,250 @@
+use std::f64::consts::PI;
+use crate::coordinates::{Margins, PolarTransformer, ScreenPoint, CELL_ASPECT};
+use crate::layout::{DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT};
+use crate::layered_canvas::{LayeredCanvas, RenderPriority};
+use crate::line_drawing::LineRenderer;
+use crate::line_style::LineStyle;
+use crate::ticks::{Tick, TickGenerator};
+use crate::data::{Dataset, DataPoint};
+use crate::color;
+
+/// Angle between radial spokes, each labelled at the rim, in degrees
+const SPOKE_STEP_DEGREES: usize = 45;
+
+/// Character for the circular grid and diagonal spokes
+const GRID_CHAR: char = '.';
+
+/// Room around the circle for the angle labels
+const POLAR_MARGINS: Margins = Margins {
+    left: 6,
+    right: 6,
+    top: 1,
+    bottom: 1,
+};
+
+/// Plot of (angle, radius) data on a circular grid, for rose curves,
+/// cardioids and antenna patterns
+pub struct PolarPlot {
+    width: usize,
+    height: usize,
+    data: Vec<DataPoint>,
+    series: Vec<(String, Vec<DataPoint>)>,
+    style: LineStyle,
+    title: String,
+    r_label: String,
+}
+
+impl PolarPlot {
+    pub fn new(dataset: &Dataset, title: &str, width: usize, height: usize) -> Self {
+        let series = dataset.series_slices()
+            .into_iter()
+            .map(|(label, points)| (label.to_string(), points.to_vec()))
+            .collect();
+
+        Self {
+            width,
+            height,
+            data: dataset.points.clone(),
+            series,
+            style: LineStyle::default(),
+            title: title.to_string(),
+            r_label: dataset.y_label.clone(),
+        }
+    }
+
+    pub fn with_style(mut self, style: LineStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn render(&self, color: Option<&str>) -> String {
+        if self.data.is_empty() {
+            return format!("{}\n\nNo data to plot\n", self.title);
+        }
+
+        let rings = self.ring_ticks();
+        let max_radius = rings.last().map_or(1.0, |tick| tick.value);
+        let transformer = PolarTransformer::new(max_radius, self.width, self.height, POLAR_MARGINS);
+
+        let mut canvas = LayeredCanvas::new(self.width, self.height);
+        self.draw_grid(&mut canvas, &transformer, &rings, max_radius);
+        self.draw_angle_labels(&mut canvas, &transformer);
+
+        // Draw each series in its own colour, connected in data order
+        let mut legend = Vec::new();
+        for (index, (label, points)) in self.series.iter().enumerate() {
+            let series_color = color::series_color(index, self.series.len(), color);
+            for segment in points.split(DataPoint::is_gap) {
+                let screen_points: Vec<ScreenPoint> = segment.iter()
+                    .filter_map(|p| transformer.polar_to_screen(p.x_numeric()?, p.y()))
+                    .collect();
+
+                if self.style.show_lines {
+                    let line_layer = canvas.get_layer(RenderPriority::Lines);
+                    for pair in screen_points.windows(2) {
+                        for point in LineRenderer::bresenham_line(pair[0], pair[1]) {
+                            line_layer.draw_point_with_color(point.col, point.row, self.style.line_char, series_color);
+                        }
+                    }
+                }
+
+                if self.style.show_points {
+                    let point_layer = canvas.get_layer(RenderPriority::Points);
+                    for point in &screen_points {
+                        point_layer.draw_point_with_color(point.col, point.row, self.style.point_char, series_color);
+                    }
+                }
+            }
+
+            let marker = if self.style.show_points { self.style.point_char } else { self.style.line_char };
+            legend.push((marker, label.as_str(), series_color));
+        }
+
+        let mut output = String::new();
+        output.push_str(&self.title);
+        output.push_str("\n\n");
+        output.push_str(&self.r_label);
+        output.push('\n');
+        output.push_str(&canvas.flatten().to_string());
+        if legend.len() > 1 {
+            output.push_str(&crate::layout::format_legend(&legend, POLAR_MARGINS.left));
+        }
+
+        output
+    }
+
+    /// Radii of the grid rings; the last one is the outer edge of the plot
+    fn ring_ticks(&self) -> Vec<Tick> {
+        let max_r = self.data.iter()
+            .map(|p| p.y().abs())
+            .filter(|r| r.is_finite())
+            .fold(0.0, f64::max);
+        let max_r = if max_r > 0.0 { max_r } else { 1.0 };
+
+        let tick_gen = TickGenerator::new(3, 5);
+        let mut ticks = tick_gen.generate_ticks(0.0, max_r);
+
+        // Extend by one step when the nice ticks stop short of the data
+        if let [.., before_last, last] = &ticks[..] {
+            if last.value < max_r {
+                let step = last.value - before_last.value;
+                ticks = tick_gen.generate_ticks(0.0, last.value + step);
+            }
+        }
+
+        ticks.retain(|tick| tick.value > 0.0);
+        ticks
+    }
+
+    fn draw_grid(&self, canvas: &mut LayeredCanvas, transformer: &PolarTransformer, rings: &[Tick], max_radius: f64) {
+        let center = transformer.center();
+
+        // Concentric rings, sampled densely enough to leave no holes
+        {
+            let axes_layer = canvas.get_layer(RenderPriority::Axes);
+            for ring in rings {
+                let fraction = ring.value / max_radius;
+                let samples = ((2.0 * PI * transformer.radius_rows() * CELL_ASPECT * fraction * 2.0).ceil() as usize).max(16);
+                for i in 0..samples {
+                    let theta = 2.0 * PI * i as f64 / samples as f64;
+                    let (col, row) = transformer.position(theta, fraction);
+                    if let Some(point) = transformer.to_screen_point(col, row) {
+                        axes_layer.draw_point(point.col, point.row, GRID_CHAR);
+                    }
+                }
+            }
+
+            // Spokes from the centre to the rim, solid along the main axes
+            for degrees in (0..360).step_by(SPOKE_STEP_DEGREES) {
+                let ch = match degrees {
+                    0 | 180 => '─',
+                    90 | 270 => '│',
+                    _ => GRID_CHAR,
+                };
+                let (col, row) = transformer.position((degrees as f64).to_radians(), 1.0);
+                if let Some(rim) = transformer.to_screen_point(col, row) {
+                    for point in LineRenderer::bresenham_line(center, rim) {
+                        axes_layer.draw_point(point.col, point.row, ch);
+                    }
+                }
+            }
+            axes_layer.draw_point(center.col, center.row, '┼');
+        }
+
+        // Ring radii along the 0° spoke, just below it
+        let label_layer = canvas.get_layer(RenderPriority::Labels);
+        for ring in rings {
+            let (col, _) = transformer.position(0.0, ring.value / max_radius);
+            let end = col.round() as usize;
+            let start = (end + 1).saturating_sub(ring.label.chars().count());
+            label_layer.draw_text(start, center.row + 1, &ring.label);
+        }
+    }
+
+    fn draw_angle_labels(&self, canvas: &mut LayeredCanvas, transformer: &PolarTransformer) {
+        let label_layer = canvas.get_layer(RenderPriority::Labels);
+
+        for degrees in (0..360).step_by(SPOKE_STEP_DEGREES) {
+            let theta = (degrees as f64).to_radians();
+            let label = format!("{}°", degrees);
+            let len = label.chars().count() as f64;
+
+            // One cell clear of the rim, growing away from the circle
+            let (col, row) = transformer.position(theta, 1.0);
+            let col = col + theta.cos() * CELL_ASPECT;
+            let row = row - theta.sin();
+            let start = if theta.cos() > 0.1 {
+                col
+            } else if theta.cos() < -0.1 {
+                col - len + 1.0
+            } else {
+                col - (len / 2.0).floor()
+            };
+
+            if let Some(point) = transformer.to_screen_point(start, row) {
+                label_layer.draw_text(point.col, point.row, &label);
+            }
+        }
+    }
+}
+
+pub fn render_polar_plot(dataset: &Dataset, title: &str, style: LineStyle, color: Option<&str>) -> String {
+    let plot = PolarPlot::new(dataset, title, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT).with_style(style);
+    plot.render(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(radius: f64) -> Dataset {
+        let points = (0..=36)
+            .map(|i| DataPoint::Numeric(i as f64 * PI / 18.0, radius))
+            .collect();
+        Dataset::new_numeric(points, "theta".to_string(), "r".to_string())
+            .with_preserve_order(true)
+            .with_polar(true)
+    }
+
+    #[test]
+    fn test_rings_cover_data() {
+        let plot = PolarPlot::new(&circle(2.3), "Circle", 80, 24);
+        let rings = plot.ring_ticks();
+
+        assert!(rings.len() >= 2);
+        assert!(rings.iter().all(|tick| tick.value > 0.0));
+        assert!(rings.last().unwrap().value >= 2.3);
+    }
+
+    #[test]
+    fn test_render_has_angle_labels_and_grid() {
+        let output = render_polar_plot(&circle(1.0), "Circle", LineStyle::default(), None);
+
+        assert!(output.starts_with("Circle\n\nr\n"));
+        for label in ["0°", "45°", "90°", "135°", "180°", "225°", "270°", "315°"] {
+            assert!(output.contains(label), "missing {}", label);
+        }
+        assert!(output.contains('┼'));
+        assert!(output.contains('●'));
+    }
+}
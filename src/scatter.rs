@@ -1,5 +1,5 @@
 use crate::coordinates::{DataBounds, CoordinateTransformer};
-use crate::layout::LayoutEngine;
+use crate::layout::{LayoutEngine, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT};
 use crate::data::{Dataset, DataPoint};
 use crate::color;
 
@@ -7,6 +7,8 @@ pub struct ScatterPlot {
     width: usize,
     height: usize,
     data: Vec<DataPoint>,
+    series: Vec<(String, Vec<DataPoint>)>,
+    is_time: bool,
     title: String,
     x_label: String,
     y_label: String,
@@ -15,11 +17,17 @@ pub struct ScatterPlot {
 impl ScatterPlot {
     pub fn new(dataset: &Dataset, title: &str, width: usize, height: usize) -> Self {
         let data = dataset.points.clone();
+        let series = dataset.series_slices()
+            .into_iter()
+            .map(|(label, points)| (label.to_string(), points.to_vec()))
+            .collect();
         
         Self {
             width,
             height,
             data,
+            series,
+            is_time: dataset.is_time,
             title: title.to_string(),
             x_label: dataset.x_label.clone(),
             y_label: dataset.y_label.clone(),
@@ -32,14 +40,14 @@ impl ScatterPlot {
         }
 
         let bounds = DataBounds::from_numeric_data_points(&self.data);
-        let layout_engine = LayoutEngine::new(self.width, self.height);
+        let layout_engine = LayoutEngine::new(self.width, self.height).with_time_x_axis(self.is_time);
         let layout = layout_engine.calculate_layout(&bounds);
         
         let transformer = CoordinateTransformer::new(
             bounds,
             self.width,
             self.height,
-            layout.margins.clone(),
+            layout.margins,
         );
         
         let mut canvas = CharCanvas::new(self.width, self.height);
@@ -47,10 +55,16 @@ impl ScatterPlot {
         self.draw_axes(&mut canvas, &layout);
         self.draw_ticks_and_labels(&mut canvas, &layout);
         
-        for point in &self.data {
-            if let Some(screen_pt) = transformer.transform_data_point(point) {
-                canvas.set_char(screen_pt.col, screen_pt.row, symbol, color);
+        // Draw each series in its own colour
+        let mut legend = Vec::new();
+        for (index, (label, points)) in self.series.iter().enumerate() {
+            let series_color = color::series_color(index, self.series.len(), color);
+            for point in points {
+                if let Some(screen_pt) = transformer.transform_data_point(point) {
+                    canvas.set_char(screen_pt.col, screen_pt.row, symbol, series_color);
+                }
             }
+            legend.push((symbol, label.as_str(), series_color));
         }
         
         let mut output = String::new();
@@ -60,6 +74,9 @@ impl ScatterPlot {
         output.push('\n');
         output.push_str(&canvas.render_with_y_labels(&layout));
         output.push_str(&crate::layout::format_x_axis_label(&self.x_label, &layout));
+        if legend.len() > 1 {
+            output.push_str(&crate::layout::format_legend(&legend, layout.plot_area.left));
+        }
         
         output
     }
@@ -188,6 +205,6 @@ impl CharCanvas {
 
 
 pub fn render_scatter_plot(dataset: &Dataset, title: &str, symbol: char, color: Option<&str>) -> String {
-    let plot = ScatterPlot::new(dataset, title, 80, 24);
+    let plot = ScatterPlot::new(dataset, title, DEFAULT_CANVAS_WIDTH, DEFAULT_CANVAS_HEIGHT);
     plot.render(symbol, color)
 }
\ No newline at end of file
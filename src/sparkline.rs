@@ -0,0 +1,256 @@
+use crate::data::{Dataset, DataPoint};
+use crate::color;
+
+/// Block characters from lowest to highest level
+pub const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Widest sparkline produced when no explicit width is requested
+pub const DEFAULT_MAX_WIDTH: usize = 80;
+
+/// Compact single-line chart without axes or title, for dashboards and prompts
+pub struct Sparkline {
+    /// (position, value) in drawing order: x for numeric series, the index otherwise.
+    /// Gaps keep their position with a NaN value.
+    points: Vec<(f64, f64)>,
+    width: Option<usize>,
+    show_summary: bool,
+}
+
+impl Sparkline {
+    pub fn new(dataset: &Dataset) -> Self {
+        // Numeric series read left to right by x; categorical and ordered series keep data order
+        let mut points: Vec<DataPoint> = dataset.points.clone();
+        if !dataset.is_categorical && !dataset.preserve_order {
+            points.sort_by(|a, b| {
+                let a_x = a.x_numeric().unwrap_or(0.0);
+                let b_x = b.x_numeric().unwrap_or(0.0);
+                a_x.partial_cmp(&b_x).unwrap()
+            });
+        }
+
+        // Spacing along x is kept, so densely sampled stretches are not drawn wider
+        let by_x = !dataset.is_categorical && !dataset.preserve_order;
+        let points = points.iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let position = if by_x { p.x_numeric().unwrap_or(0.0) } else { i as f64 };
+                (position, if p.is_gap() { f64::NAN } else { p.y() })
+            })
+            .collect();
+
+        Self {
+            points,
+            width: None,
+            show_summary: false,
+        }
+    }
+
+    pub fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_summary(mut self, show_summary: bool) -> Self {
+        self.show_summary = show_summary;
+        self
+    }
+
+    pub fn render(&self, color: Option<&str>) -> String {
+        let values: Vec<f64> = self.points.iter().map(|p| p.1).filter(|y| !y.is_nan()).collect();
+        let Some(&last) = values.last() else {
+            return "No data to plot\n".to_string();
+        };
+
+        let width = self.width.unwrap_or_else(|| self.points.len().min(DEFAULT_MAX_WIDTH));
+        let samples = resample(&self.points, width);
+
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        let mut output = String::new();
+        for value in samples {
+            if value.is_nan() {
+                output.push(' ');
+                continue;
+            }
+            let ch = spark_char(value, min, max);
+            match color.and_then(|c| color::apply_color(ch, c)) {
+                Some(colored_char) => output.push_str(&colored_char),
+                None => output.push(ch),
+            }
+        }
+
+        if self.show_summary {
+            output.push_str(&format!(
+                " min {} max {} last {}",
+                format_value(min),
+                format_value(max),
+                format_value(last),
+            ));
+        }
+
+        output.push('\n');
+        output
+    }
+}
+
+/// Resample a series to exactly `width` values. Each point goes to the column
+/// nearest its position, the first and last positions taking the end columns;
+/// columns holding several points show their average, and empty columns are
+/// interpolated from their neighbours. A column holding only gaps is NaN, as
+/// are the empty columns bordering it.
+fn resample(points: &[(f64, f64)], width: usize) -> Vec<f64> {
+    if points.is_empty() || width == 0 {
+        return Vec::new();
+    }
+
+    let first = points[0].0;
+    let span = points[points.len() - 1].0 - first;
+    // Per column: sum and count of values, and the number of gaps
+    let mut sums = vec![(0.0, 0usize, 0usize); width];
+    for (position, value) in points {
+        let column = if span > 0.0 {
+            ((position - first) / span * (width - 1) as f64).round() as usize
+        } else {
+            0
+        };
+        let (sum, count, gaps) = &mut sums[column.min(width - 1)];
+        if value.is_nan() {
+            *gaps += 1;
+        } else {
+            *sum += value;
+            *count += 1;
+        }
+    }
+
+    let filled: Vec<(usize, f64)> = sums.iter()
+        .enumerate()
+        .filter(|(_, (_, count, gaps))| *count + *gaps > 0)
+        .map(|(column, (sum, count, _))| (column, if *count > 0 { sum / *count as f64 } else { f64::NAN }))
+        .collect();
+    (0..width)
+        .map(|column| {
+            let next = filled.partition_point(|(c, _)| *c < column);
+            match (next.checked_sub(1).map(|i| filled[i]), filled.get(next)) {
+                (_, Some(&(c, value))) if c == column => value,
+                (Some((c0, v0)), Some(&(c1, v1))) => {
+                    let t = (column - c0) as f64 / (c1 - c0) as f64;
+                    v0 * (1.0 - t) + v1 * t
+                }
+                (Some((_, value)), None) | (None, Some(&(_, value))) => value,
+                (None, None) => f64::NAN,
+            }
+        })
+        .collect()
+}
+
+/// Map a value onto one of the block characters given the series extremes
+fn spark_char(value: f64, min: f64, max: f64) -> char {
+    let range = max - min;
+    if range <= 0.0 {
+        // Flat series sit in the middle of the cell
+        return SPARK_CHARS[SPARK_CHARS.len() / 2 - 1];
+    }
+
+    let level = ((value - min) / range * (SPARK_CHARS.len() - 1) as f64).round() as usize;
+    SPARK_CHARS[level.min(SPARK_CHARS.len() - 1)]
+}
+
+/// Format an annotation value compactly, keeping a few significant digits
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+
+    let precision = if value.abs() >= 1.0 {
+        2
+    } else {
+        ((-value.abs().log10()).ceil() as usize + 2).min(6)
+    };
+    let formatted = format!("{:.prec$}", value, prec = precision);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub fn render_sparkline(dataset: &Dataset, width: Option<usize>, show_summary: bool, color: Option<&str>) -> String {
+    let sparkline = Sparkline::new(dataset)
+        .with_width(width)
+        .with_summary(show_summary);
+    sparkline.render(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numeric_dataset(values: &[f64]) -> Dataset {
+        let points = values.iter()
+            .enumerate()
+            .map(|(i, y)| DataPoint::Numeric(i as f64, *y))
+            .collect();
+        Dataset::new_numeric(points, "x".to_string(), "y".to_string())
+    }
+
+    #[test]
+    fn test_sparkline_levels() {
+        let dataset = numeric_dataset(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(render_sparkline(&dataset, None, false, None), "▁▂▃▄▅▆▇█\n");
+    }
+
+    #[test]
+    fn test_sparkline_width_and_summary() {
+        let dataset = numeric_dataset(&[3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0]);
+        let output = render_sparkline(&dataset, Some(4), true, None);
+        let (spark, summary) = output.trim_end().split_once(' ').unwrap();
+        assert_eq!(spark.chars().count(), 4);
+        assert_eq!(summary, "min 1 max 9 last 6");
+    }
+
+    fn evenly(values: &[f64]) -> Vec<(f64, f64)> {
+        values.iter().enumerate().map(|(i, y)| (i as f64, *y)).collect()
+    }
+
+    #[test]
+    fn test_resample_shrink_and_stretch() {
+        assert_eq!(resample(&evenly(&[1.0, 3.0, 5.0, 7.0]), 2), vec![2.0, 6.0]);
+        assert_eq!(resample(&evenly(&[0.0, 10.0]), 3), vec![0.0, 5.0, 10.0]);
+        assert_eq!(resample(&evenly(&[4.0]), 3), vec![4.0, 4.0, 4.0]);
+    }
+
+    #[test]
+    fn test_resample_follows_x_spacing() {
+        // Adaptive sampling crowds points where the curve bends; they share a column
+        let points = [(0.0, 0.0), (0.1, 8.0), (0.2, 8.0), (0.3, 0.0), (4.0, 4.0), (8.0, 8.0)];
+        assert_eq!(resample(&points, 3), vec![4.0, 4.0, 8.0]);
+        let output = render_sparkline(&numeric_dataset(&[0.0, 1.0]), Some(3), false, None);
+        assert_eq!(output, "▁▅█\n");
+    }
+
+    #[test]
+    fn test_gaps_are_blank() {
+        let dataset = Dataset::new_numeric(
+            vec![
+                DataPoint::Numeric(1.0, 1.0),
+                DataPoint::gap(2.0),
+                DataPoint::Numeric(3.0, 3.0),
+                DataPoint::Numeric(4.0, 2.0),
+            ],
+            "x".to_string(),
+            "y".to_string(),
+        );
+        assert_eq!(render_sparkline(&dataset, None, false, None), "▁ █▅\n");
+        assert_eq!(render_sparkline(&dataset, Some(7), true, None), "▁   █▆▅ min 1 max 3 last 2\n");
+        let trailing = Dataset::new_numeric(
+            vec![DataPoint::Numeric(0.0, 5.0), DataPoint::gap(1.0)],
+            "x".to_string(),
+            "y".to_string(),
+        );
+        assert_eq!(render_sparkline(&trailing, None, true, None), "▄  min 5 max 5 last 5\n");
+    }
+
+    #[test]
+    fn test_flat_series_and_format() {
+        assert_eq!(spark_char(2.0, 2.0, 2.0), '▄');
+        assert_eq!(format_value(2.5), "2.5");
+        assert_eq!(format_value(0.01234), "0.0123");
+    }
+}
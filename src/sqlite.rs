@@ -0,0 +1,209 @@
+use anyhow::{Result, anyhow};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use crate::data::{Dataset, PointCollector};
+use crate::input::ReadOptions;
+use crate::timestamp;
+
+/// How the values of a result column are read, from its declared type
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Affinity {
+    /// INTEGER, REAL, NUMERIC and the like
+    Numeric,
+    /// TEXT, CHAR and CLOB: labels, even when they hold digits
+    Text,
+    /// DATE, DATETIME and TIMESTAMP: dates as text or epoch seconds
+    Time,
+    /// Expressions and untyped columns, read by each value's storage class
+    Any,
+}
+
+impl Affinity {
+    fn from_declared(declared: Option<&str>) -> Self {
+        let Some(declared) = declared.map(str::to_ascii_uppercase) else {
+            return Affinity::Any;
+        };
+        if declared.contains("DATE") || declared.contains("TIME") {
+            Affinity::Time
+        } else if ["CHAR", "CLOB", "TEXT"].iter().any(|t| declared.contains(t)) {
+            Affinity::Text
+        } else if ["INT", "REAL", "FLOA", "DOUB", "NUM", "DEC"].iter().any(|t| declared.contains(t)) {
+            Affinity::Numeric
+        } else {
+            Affinity::Any
+        }
+    }
+}
+
+/// Run a query against a SQLite file, given as "path.db?query=SELECT ...",
+/// and plot its result: the first column is x and every further column is a
+/// y series named after it. The database is opened read-only.
+pub fn parse_sqlite(spec: &str, options: &ReadOptions) -> Result<Dataset> {
+    let (path, query) = spec.split_once('?')
+        .and_then(|(path, rest)| Some((path, rest.strip_prefix("query=")?)))
+        .ok_or_else(|| anyhow!("SQLite sources look like sqlite:app.db?query=SELECT x, y FROM t, got: sqlite:{}", spec))?;
+
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| anyhow!("Cannot open SQLite database {}: {}", path, e))?;
+    let mut statement = connection.prepare(query)
+        .map_err(|e| anyhow!("Invalid query for {}: {}", path, e))?;
+
+    let columns: Vec<(String, Affinity)> = statement.columns().iter()
+        .map(|column| (column.name().to_string(), Affinity::from_declared(column.decl_type())))
+        .collect();
+    if columns.len() < 2 {
+        return Err(anyhow!(
+            "The query must return at least two columns, x and y, but returns {}",
+            columns.len()
+        ));
+    }
+
+    let x_affinity = columns[0].1;
+    let mut collectors: Vec<PointCollector> = columns[1..].iter()
+        .map(|_| PointCollector::new().with_downsample(options.downsample))
+        .collect();
+    let mut is_time = x_affinity == Affinity::Time;
+
+    let mut rows = statement.query([])?;
+    let mut row_number = 0;
+    while let Some(row) = rows.next()? {
+        row_number += 1;
+        let (x_text, x) = match x_value(row.get_ref(0)?, x_affinity) {
+            Some((text, x, time)) => {
+                is_time |= time;
+                (text, x)
+            }
+            None => (String::new(), None),
+        };
+
+        for (index, collector) in collectors.iter_mut().enumerate() {
+            let y = match y_value(row.get_ref(index + 1)?) {
+                Ok(y) => y,
+                Err(value) => {
+                    let error = anyhow!(
+                        "Invalid number {} in query result, row {}, column '{}'",
+                        value,
+                        row_number,
+                        columns[index + 1].0
+                    );
+                    options.on_bad_row.handle(error)?;
+                    None
+                }
+            };
+            collector.push(&x_text, x, y);
+        }
+    }
+
+    let x_label = columns[0].0.clone();
+    let mut datasets = Vec::new();
+    for (collector, (y_label, _)) in collectors.into_iter().zip(&columns[1..]) {
+        let is_time = is_time && !collector.is_categorical();
+        datasets.push(collector.finish(x_label.clone(), y_label.clone()).with_time(is_time));
+    }
+    if datasets.len() == 1 {
+        return Ok(datasets.remove(0));
+    }
+    if datasets.iter().any(|d| d.is_categorical) {
+        return Err(anyhow!(
+            "Several y columns need a numeric x column; '{}' holds labels, so select one y column",
+            x_label
+        ));
+    }
+    let labels: Vec<String> = columns[1..].iter().map(|(name, _)| name.clone()).collect();
+    let datasets = datasets.into_iter().zip(&labels)
+        .map(|(dataset, label)| dataset.with_series_label(label))
+        .collect();
+    Dataset::combine(datasets, &labels.join(", "))
+}
+
+/// An x value as its text, its number when it has one, and whether it was
+/// read as a timestamp; `None` when NULL
+fn x_value(value: ValueRef, affinity: Affinity) -> Option<(String, Option<f64>, bool)> {
+    let number = |x: f64| Some((x.to_string(), Some(x), affinity == Affinity::Time));
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(x) if affinity == Affinity::Text => Some((x.to_string(), None, false)),
+        ValueRef::Real(x) if affinity == Affinity::Text => Some((x.to_string(), None, false)),
+        ValueRef::Integer(x) => number(x as f64),
+        ValueRef::Real(x) => number(x),
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => {
+            let text = String::from_utf8_lossy(bytes).into_owned();
+            let (x, time) = match affinity {
+                Affinity::Text => (None, false),
+                Affinity::Time => (timestamp::parse_timestamp(&text), true),
+                Affinity::Numeric | Affinity::Any => (text.trim().parse().ok(), false),
+            };
+            Some((text, x, time && x.is_some()))
+        }
+    }
+}
+
+/// A y value: `None` for NULL, numbers as stored, and text parsed as a
+/// number, with the offending text as the error when it is not one
+fn y_value(value: ValueRef) -> std::result::Result<Option<f64>, String> {
+    match value {
+        ValueRef::Null => Ok(None),
+        ValueRef::Integer(y) => Ok(Some(y as f64)),
+        ValueRef::Real(y) => Ok(Some(y)),
+        ValueRef::Text(bytes) => {
+            let text = String::from_utf8_lossy(bytes);
+            if crate::input::is_missing(&text) {
+                return Ok(None);
+            }
+            text.trim().parse().map(Some).map_err(|_| format!("'{}'", text))
+        }
+        ValueRef::Blob(bytes) => Err(format!("<{}-byte blob>", bytes.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataPoint;
+
+    fn database(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("fastplot-{}-{}.db", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        let connection = Connection::open(&path).unwrap();
+        connection.execute_batch("
+            CREATE TABLE readings (taken_at DATETIME, sensor TEXT, celsius REAL, humidity INTEGER);
+            INSERT INTO readings VALUES ('2024-03-01 10:00:00', 'attic', 21.5, 40);
+            INSERT INTO readings VALUES ('2024-03-01 11:00:00', 'cellar', NULL, 65);
+            INSERT INTO readings VALUES ('2024-03-01 12:00:00', '3', 23.0, 42);
+        ").unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_time_column_and_several_series() {
+        let path = database("series");
+        let spec = format!("{}?query=SELECT taken_at, celsius, humidity FROM readings", path);
+        let dataset = parse_sqlite(&spec, &ReadOptions::new()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(dataset.is_time);
+        assert_eq!(dataset.series.iter().map(|s| s.label.as_str()).collect::<Vec<_>>(), vec!["celsius", "humidity"]);
+        assert_eq!(dataset.points[0].x_numeric(), Some(1_709_287_200.0));
+        assert!(dataset.points[1].is_gap());
+        assert_eq!(dataset.points[5].y(), 42.0);
+    }
+
+    #[test]
+    fn test_text_column_is_categorical() {
+        let path = database("categories");
+        let spec = format!("{}?query=SELECT sensor, humidity FROM readings ORDER BY humidity", path);
+        let dataset = parse_sqlite(&spec, &ReadOptions::new()).unwrap();
+        assert!(dataset.is_categorical);
+        // A declared TEXT column stays categorical even where it holds digits
+        assert_eq!(dataset.categories, vec!["attic", "3", "cellar"]);
+        assert!(matches!(&dataset.points[2], DataPoint::Categorical(sensor, y) if sensor == "cellar" && *y == 65.0));
+
+        let spec = format!("{}?query=SELECT humidity * 2 FROM readings", path);
+        assert!(parse_sqlite(&spec, &ReadOptions::new()).unwrap_err().to_string().contains("at least two columns"));
+        let spec = format!("{}?query=SELECT sensor, celsius, humidity FROM readings", path);
+        assert!(parse_sqlite(&spec, &ReadOptions::new()).unwrap_err().to_string().contains("need a numeric x column"));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(parse_sqlite("app.db", &ReadOptions::new()).unwrap_err().to_string().contains("sqlite:app.db?query="));
+    }
+}
@@ -1,3 +1,6 @@
+use chrono::{DateTime, Datelike, NaiveDate};
+use crate::timestamp;
+
 #[derive(Debug, Clone)]
 pub struct Tick {
     pub value: f64,
@@ -22,7 +25,6 @@ impl Default for TickGenerator {
 }
 
 impl TickGenerator {
-    #[allow(dead_code)]
     pub fn new(min_ticks: usize, max_ticks: usize) -> Self {
         Self { min_ticks, max_ticks }
     }
@@ -114,6 +116,121 @@ impl TickGenerator {
     }
 }
 
+/// Nice tick spacings for time axes, in seconds (one second up to two weeks)
+const TIME_STEPS: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 15.0, 30.0,
+    60.0, 120.0, 300.0, 600.0, 900.0, 1800.0,
+    3600.0, 7200.0, 10800.0, 21600.0, 43200.0,
+    86400.0, 172800.0, 604800.0, 1209600.0,
+];
+
+/// Nice tick spacings in calendar months, used between weekly and yearly ticks
+const MONTH_STEPS: &[u32] = &[1, 2, 3, 6];
+
+const SECONDS_PER_MONTH: f64 = 30.44 * 86400.0;
+const SECONDS_PER_YEAR: f64 = 365.2425 * 86400.0;
+
+/// Tick generator for axes holding timestamps in seconds since the Unix epoch.
+/// Ticks fall on calendar boundaries (whole minutes, days, months, years);
+/// year ticks reuse the numeric `TickGenerator` to pick a nice spacing.
+#[derive(Default)]
+pub struct TimeTickGenerator {
+    ticks: TickGenerator,
+}
+
+impl TimeTickGenerator {
+    pub fn generate_ticks(&self, min: f64, max: f64) -> Vec<Tick> {
+        if min >= max {
+            return vec![Self::tick(min, "%Y-%m-%d %H:%M")];
+        }
+
+        let raw_step = (max - min) / (self.ticks.max_ticks - 1) as f64;
+
+        if let Some(step) = TIME_STEPS.iter().copied().find(|step| *step >= raw_step) {
+            let mut ticks = Vec::new();
+            let mut index = (min / step).ceil();
+            while index * step <= max {
+                let value = index * step;
+                // Midnight ticks carry the date so multi-day ranges stay readable
+                let format = if value % 86400.0 == 0.0 { "%m-%d" } else { Self::label_format(step) };
+                ticks.push(Self::tick(value, format));
+                index += 1.0;
+            }
+            ticks
+        } else if let Some(months) = MONTH_STEPS.iter().copied().find(|m| *m as f64 * SECONDS_PER_MONTH >= raw_step) {
+            self.month_ticks(min, max, months)
+        } else {
+            self.year_ticks(min, max)
+        }
+    }
+
+    fn month_ticks(&self, min: f64, max: f64, months: u32) -> Vec<Tick> {
+        let Some(start) = DateTime::from_timestamp(min.floor() as i64, 0) else {
+            return Vec::new();
+        };
+
+        // Walk month starts from the first one at or after `min`, aligned to the step
+        let step = months as i32;
+        let mut month_index = start.year() * 12 + start.month0() as i32;
+        if Self::month_start(month_index).is_some_and(|value| value < min) {
+            month_index += 1;
+        }
+        month_index += (step - month_index.rem_euclid(step)) % step;
+
+        let mut ticks = Vec::new();
+        while let Some(value) = Self::month_start(month_index) {
+            if value > max {
+                break;
+            }
+            ticks.push(Self::tick(value, "%Y-%m"));
+            month_index += step;
+        }
+        ticks
+    }
+
+    fn year_ticks(&self, min: f64, max: f64) -> Vec<Tick> {
+        let min_year = 1970.0 + min / SECONDS_PER_YEAR;
+        let max_year = 1970.0 + max / SECONDS_PER_YEAR;
+
+        let mut years: Vec<i32> = self.ticks.generate_ticks(min_year, max_year)
+            .iter()
+            .filter(|tick| tick.value.fract().abs() < 1e-9)
+            .map(|tick| tick.value.round() as i32)
+            .collect();
+        years.dedup();
+
+        years.into_iter()
+            .filter_map(|year| Self::month_start(year * 12))
+            .filter(|value| *value >= min && *value <= max)
+            .map(|value| Self::tick(value, "%Y"))
+            .collect()
+    }
+
+    /// Epoch seconds at midnight on the first day of a month counted from year 0
+    fn month_start(month_index: i32) -> Option<f64> {
+        let date = NaiveDate::from_ymd_opt(month_index.div_euclid(12), month_index.rem_euclid(12) as u32 + 1, 1)?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp() as f64)
+    }
+
+    fn label_format(step: f64) -> &'static str {
+        if step < 60.0 {
+            "%H:%M:%S"
+        } else if step < 86400.0 {
+            "%H:%M"
+        } else {
+            "%m-%d"
+        }
+    }
+
+    fn tick(value: f64, format: &str) -> Tick {
+        Tick {
+            value,
+            label: timestamp::format_timestamp(value, format),
+            is_major: true,
+        }
+    }
+}
+
 pub struct CategoricalTickGenerator {
     max_label_width: usize,
 }
@@ -155,7 +272,7 @@ impl CategoricalTickGenerator {
         // Smart truncation: keep first and last chars with "..." in middle
         let ellipsis = "...";
         let available_chars = max_len - ellipsis.len();
-        let first_chars = (available_chars + 1) / 2;
+        let first_chars = available_chars.div_ceil(2);
         let last_chars = available_chars / 2;
         
         let first_part: String = text.chars().take(first_chars).collect();
@@ -212,4 +329,29 @@ mod tests {
         assert_eq!(gen.format_tick_label(0.0, 0.1), "0");
         assert_eq!(gen.format_tick_label(2.5, 0.5), "2.5");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_time_ticks_hours() {
+        let gen = TimeTickGenerator::default();
+        let ticks = gen.generate_ticks(0.0, 6.0 * 3600.0);
+
+        assert_eq!(ticks[0].label, "01-01");
+        assert_eq!(ticks[1].label, "01:00");
+        assert!(ticks.iter().all(|t| t.value % 3600.0 == 0.0));
+    }
+
+    #[test]
+    fn test_time_ticks_months_and_years() {
+        let gen = TimeTickGenerator::default();
+
+        // Mid-January to mid-December 1970: ticks every two months on the first
+        let ticks = gen.generate_ticks(14.0 * 86400.0, 348.0 * 86400.0);
+        let labels: Vec<&str> = ticks.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["1970-03", "1970-05", "1970-07", "1970-09", "1970-11"]);
+
+        // A decade: year ticks on January 1st
+        let ticks = gen.generate_ticks(0.0, 10.0 * SECONDS_PER_YEAR);
+        assert_eq!(ticks[0].label, "1970");
+        assert!(ticks.iter().all(|t| t.label.len() == 4));
+    }
+}
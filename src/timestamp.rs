@@ -0,0 +1,116 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// Date-time layouts accepted in data files, tried in order
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+/// Date-only layouts accepted in data files, tried in order
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d"];
+
+/// Layouts with a UTC offset, such as the Apache access log's
+const OFFSET_DATETIME_FORMATS: &[&str] = &["%d/%b/%Y:%H:%M:%S %z"];
+
+/// Time-of-day layouts, as printed by many log formats
+const TIME_FORMATS: &[&str] = &["%H:%M:%S%.f", "%H:%M"];
+
+/// Parse a timestamp into seconds since the Unix epoch (UTC)
+///
+/// Accepts RFC 3339 strings, common date and date-time layouts, and plain
+/// numbers that match none of them, which are taken to already be epoch
+/// seconds. A time of day without a date is placed on 1970-01-01, so it
+/// counts seconds since midnight.
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(to_seconds(&datetime.naive_utc()));
+    }
+
+    for format in DATETIME_FORMATS {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(to_seconds(&datetime));
+        }
+    }
+
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return Some(to_seconds(&date.and_hms_opt(0, 0, 0)?));
+        }
+    }
+
+    for format in OFFSET_DATETIME_FORMATS {
+        if let Ok(datetime) = DateTime::parse_from_str(text, format) {
+            return Some(to_seconds(&datetime.naive_utc()));
+        }
+    }
+
+    for format in TIME_FORMATS {
+        if let Ok(time) = NaiveTime::parse_from_str(text, format) {
+            return Some(to_seconds(&NaiveDate::default().and_time(time)));
+        }
+    }
+
+    // Last, so that compact dates such as 20240101 are not read as seconds
+    text.parse::<f64>().ok().filter(|seconds| seconds.is_finite())
+}
+
+/// Format epoch seconds (UTC) with a chrono format string
+pub fn format_timestamp(seconds: f64, format: &str) -> String {
+    match DateTime::from_timestamp_millis((seconds * 1000.0).round() as i64) {
+        Some(datetime) => datetime.format(format).to_string(),
+        None => format!("{}", seconds),
+    }
+}
+
+fn to_seconds(datetime: &NaiveDateTime) -> f64 {
+    datetime.and_utc().timestamp_millis() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dates() {
+        assert_eq!(parse_timestamp("1970-01-02"), Some(86400.0));
+        assert_eq!(parse_timestamp("1970/01/01 00:01"), Some(60.0));
+        assert_eq!(parse_timestamp("1970-01-01T00:00:01.5"), Some(1.5));
+        assert_eq!(parse_timestamp("1970-01-01T01:00:00+01:00"), Some(0.0));
+        assert_eq!(parse_timestamp("02/Jan/1970:01:00:00 +0100"), Some(86400.0));
+    }
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_timestamp("12:01:02"), Some(43262.0));
+        assert_eq!(parse_timestamp("00:00:01.250"), Some(1.25));
+        assert_eq!(parse_timestamp("23:59"), Some(86340.0));
+        assert_eq!(parse_timestamp("25:00"), None);
+    }
+
+    #[test]
+    fn test_parse_epoch_and_invalid() {
+        assert_eq!(parse_timestamp("1700000000"), Some(1_700_000_000.0));
+        assert_eq!(parse_timestamp("1700000000.25"), Some(1_700_000_000.25));
+        assert_eq!(parse_timestamp("-3600"), Some(-3600.0));
+        // Eight digits are a compact date, unless they are not a valid one
+        assert_eq!(parse_timestamp("20240101"), Some(1_704_067_200.0));
+        assert_eq!(parse_timestamp("99999999"), Some(99_999_999.0));
+        assert_eq!(parse_timestamp("yesterday"), None);
+        assert_eq!(parse_timestamp(""), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(86400.0 * 31.0, "%Y-%m-%d"), "1970-02-01");
+        assert_eq!(format_timestamp(3723.0, "%H:%M:%S"), "01:02:03");
+    }
+}